@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, Criterion};
+use daisychain::prelude::*;
+
+const LEN: usize = 100_000;
+
+fn item(s: &str) -> Result<(&str, u32), ParsingError> {
+    Cursor::from(s)
+        .digits(1..)
+        .parse_selection()
+        .maybe(",")
+        .validate()
+}
+
+fn input() -> String {
+    (0..LEN).map(|i| format!("{i},")).collect()
+}
+
+pub fn bench_parse_struct_vec(c: &mut Criterion) {
+    let s = input();
+    c.bench_function("parse_struct_vec_100k", |b| {
+        b.iter(|| {
+            let (_c, vec) = Cursor::from(black_box(s.as_str()))
+                .parse_struct_vec(item)
+                .validate()
+                .unwrap();
+            assert_eq!(vec.len(), LEN);
+        })
+    });
+}
+
+pub fn bench_iter_struct(c: &mut Criterion) {
+    let s = input();
+    c.bench_function("iter_struct_100k", |b| {
+        b.iter(|| {
+            let n = Cursor::from(black_box(s.as_str()))
+                .iter_struct(item)
+                .filter_map(Result::ok)
+                .count();
+            assert_eq!(n, LEN);
+        })
+    });
+}
+
+criterion_group!(benches, bench_parse_struct_vec, bench_iter_struct);
+
+fn main() {
+    env_logger::init();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,149 @@
+//! `#[derive(Parse)]` for simple fixed-format structs, re-exported by daisychain
+//! behind the "derive" feature.
+//!
+//! The struct attribute lists the steps of the cursor chain. Steps which produce
+//! a value (`digits`, `word`) are parsed with FromStr into the named fields in order,
+//! the others (`lit`, `ws`) only consume input.
+//!
+//! ```ignore
+//! #[derive(Parse)]
+//! #[daisychain(digits(2), lit(":"), digits(2))]
+//! struct Time {
+//!     hours: u8,
+//!     mins: u8,
+//! }
+//! ```
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parenthesized, parse::ParseStream, parse_macro_input, punctuated::Punctuated, Data,
+    DeriveInput, Error, Fields, Ident, LitInt, LitStr, Token,
+};
+
+enum Step {
+    Digits(Option<LitInt>),
+    Word,
+    Lit(LitStr),
+    Ws,
+}
+
+impl Step {
+    fn is_value(&self) -> bool {
+        matches!(self, Step::Digits(_) | Step::Word)
+    }
+
+    fn lexer(&self) -> TokenStream {
+        match self {
+            Step::Digits(Some(n)) => quote!(.digits(#n..=#n)),
+            Step::Digits(None) => quote!(.digits(1..)),
+            Step::Word => quote!(.word()),
+            Step::Lit(s) => quote!(.text(#s)),
+            Step::Ws => quote!(.ws()),
+        }
+    }
+}
+
+impl syn::parse::Parse for Step {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let args = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content)
+        } else {
+            None
+        };
+        match (name.to_string().as_str(), args) {
+            ("digits", None) => Ok(Step::Digits(None)),
+            ("digits", Some(args)) => Ok(Step::Digits(Some(args.parse()?))),
+            ("word", None) => Ok(Step::Word),
+            ("lit", Some(args)) => Ok(Step::Lit(args.parse()?)),
+            ("ws", None) => Ok(Step::Ws),
+            _ => Err(Error::new(
+                name.span(),
+                "expected one of digits, digits(n), word, lit(\"..\") or ws",
+            )),
+        }
+    }
+}
+
+#[proc_macro_derive(Parse, attributes(daisychain))]
+pub fn derive_parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "derive(Parse) does not support generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => f.named.iter().collect::<Vec<_>>(),
+            _ => return Err(Error::new_spanned(name, "derive(Parse) needs named fields")),
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "derive(Parse) only supports structs",
+            ))
+        }
+    };
+    let attr = input
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("daisychain"))
+        .ok_or_else(|| Error::new_spanned(name, "missing #[daisychain(..)] attribute"))?;
+    let steps = attr.parse_args_with(Punctuated::<Step, Token![,]>::parse_terminated)?;
+    let values = steps.iter().filter(|s| s.is_value()).count();
+    if values != fields.len() {
+        return Err(Error::new_spanned(
+            attr,
+            format!(
+                "{values} value step(s) (digits/word) for {} field(s)",
+                fields.len()
+            ),
+        ));
+    }
+
+    let mut fields_iter = fields.iter();
+    let mut chain = vec![];
+    let mut inits = vec![];
+    for step in &steps {
+        let lexer = step.lexer();
+        if !step.is_value() {
+            chain.push(quote!(let c = c #lexer;));
+            continue;
+        }
+        let field = fields_iter.next().expect("counted above");
+        let (ident, ty) = (field.ident.as_ref().expect("named field"), &field.ty);
+        let var = format_ident!("__{}", ident);
+        chain.push(quote! {
+            let (c, #var) = ::daisychain::__derive::take(c #lexer .parse_selection::<#ty>())?;
+        });
+        inits.push(quote!(#ident: #var));
+    }
+
+    Ok(quote! {
+        impl<'c> ::daisychain::prelude::Parse<'c> for #name {
+            fn parse(
+                c: ::daisychain::prelude::Cursor<'c>,
+            ) -> ::core::result::Result<
+                (::daisychain::prelude::Cursor<'c>, Self),
+                ::daisychain::prelude::ParsingError,
+            > {
+                #[allow(unused_imports)]
+                use ::daisychain::prelude::{Matchable, Selectable};
+                #(#chain)*
+                let c = ::daisychain::__derive::check(c)?;
+                ::core::result::Result::Ok((c, Self { #(#inits),* }))
+            }
+        }
+    })
+}
@@ -1,10 +1,11 @@
-use std::str::FromStr;
-use std::{fmt::Debug, marker::PhantomData};
+use alloc::{format, string::String};
+use core::str::FromStr;
+use core::{fmt::Debug, marker::PhantomData};
 
 use log::Level::Trace;
 use log::{log_enabled, trace};
 
-use crate::{prelude::ParsingError, LABEL, LOG_TARGET};
+use crate::{error, prelude::ParsingError, set_log_label, LOG_TARGET};
 
 fn type_suffix(type_name: &str) -> &str {
     if let Some(i) = type_name.rfind("::") {
@@ -22,8 +23,8 @@ pub trait Parser<'a>: Sized {
     fn name(&self, indent: &str) -> String {
         format!(
             "{indent}parser({input}) -> Result<(), {error}>",
-            input = std::any::type_name::<Self::Input>(),
-            error = std::any::type_name::<Self::Error>()
+            input = core::any::type_name::<Self::Input>(),
+            error = core::any::type_name::<Self::Error>()
         )
     }
 
@@ -33,9 +34,9 @@ pub trait Parser<'a>: Sized {
     //     self.parse(inp).map(|(i,t)| (i,t.detuple()))
     // }
 
-    fn chain_parser<T, P2: Parser<'a, Output = T>>(self, p2: P2) -> Chain<'a, Self, P2>
+    fn chain_parser<T, P2>(self, p2: P2) -> Chain<'a, Self, P2>
     where
-        P2: Parser<'a, Input = Self::Input, Error = Self::Error>,
+        P2: Parser<'a, Output = T, Input = Self::Input, Error = Self::Error>,
         // Self::Input: Clone,
         // P2: Parser<'a, Input = Self::Input, Error = Self::Error>,
         // (Self::Output, T): ConcatTuple<Self::Output, P2::Input>,
@@ -63,9 +64,9 @@ pub trait StrParser<'a, O>: Parser<'a, Input = &'a str, Output = O, Error = Pars
 
     fn debug_context(self, span_name: &'static str) -> Self {
         if log_enabled!(target: LOG_TARGET, Trace) {
-            LABEL.with(|f| f.set("")); // blank the span name before logging
+            set_log_label(""); // blank the span name before logging
                                        // self.log_success("debug_context", span_name);
-            LABEL.with(|f| f.set(span_name));
+            set_log_label(span_name);
         }
 
         self
@@ -104,13 +105,7 @@ where
     fn validate(&mut self, inp: Self::Input) -> Result<Self::Output, Self::Error> {
         match inp.parse::<T>() {
             Ok(t) => Ok((inp, t)),
-            Err(..) => {
-                let e = ParsingError::NoMatch {
-                    action: "FromStr",
-                    args: "",
-                };
-                Err(e)
-            }
+            Err(_) => Err(error::from_str_failure::<T>(inp)),
         }
     }
 }
@@ -141,7 +136,8 @@ where
                 .map(|i| &s[i..])
                 .ok_or(ParsingError::NoMatch {
                     action: "",
-                    args: "",
+                    args: "".into(),
+                    offset: 0,
                 })
         })
     }
@@ -178,12 +174,12 @@ where
         let indent = indent.replace("└──", "|  ");
         format!(
             "{indent}chain ({input}) -> Result<{output}, {error}>\n{s}\n{t}\n{indent}",
-            input = std::any::type_name::<Self::Input>(),
-            output = std::any::type_name::<Self::Output>(),
-            error = type_suffix(std::any::type_name::<Self::Error>()),
-            // s = std::any::type_name::<P1>(),
+            input = core::any::type_name::<Self::Input>(),
+            output = core::any::type_name::<Self::Output>(),
+            error = type_suffix(core::any::type_name::<Self::Error>()),
+            // s = core::any::type_name::<P1>(),
             s = self.p1.name(&format!("{indent}└──")),
-            // t = std::any::type_name::<P2>(),
+            // t = core::any::type_name::<P2>(),
             t = self.p2.name(&format!("{indent}└──")),
         )
     }
@@ -356,10 +352,10 @@ where
     fn name(&self, indent: &str) -> String {
         format!(
             "{indent}Lex {func}({input}) -> Result<({input}, {t}), {error}>",
-            func = type_suffix(std::any::type_name::<Self>()),
-            t = type_suffix(std::any::type_name::<()>()),
-            input = std::any::type_name::<Self::Input>(),
-            error = type_suffix(std::any::type_name::<Self::Error>())
+            func = type_suffix(core::any::type_name::<Self>()),
+            t = type_suffix(core::any::type_name::<()>()),
+            input = core::any::type_name::<Self::Input>(),
+            error = type_suffix(core::any::type_name::<Self::Error>())
         )
     }
     fn validate(&mut self, s: &'a str) -> Result<&'a str, ParsingError> {
@@ -382,10 +378,10 @@ where
     fn name(&self, indent: &str) -> String {
         format!(
             "{indent}Par {func}({input}) -> Result<({input}, {t}), {error}>",
-            func = type_suffix(std::any::type_name::<Self>()),
-            t = type_suffix(std::any::type_name::<T>()),
-            input = std::any::type_name::<Self::Input>(),
-            error = type_suffix(std::any::type_name::<Self::Error>())
+            func = type_suffix(core::any::type_name::<Self>()),
+            t = type_suffix(core::any::type_name::<T>()),
+            input = core::any::type_name::<Self::Input>(),
+            error = type_suffix(core::any::type_name::<Self::Error>())
         )
     }
     fn validate(&mut self, s: &'a str) -> Result<(&'a str, T), ParsingError> {
@@ -418,8 +414,8 @@ impl<'a> Parser<'a> for SP {
     fn name(&self, indent: &str) -> String {
         format!(
             "{indent}SP({input}) -> Result<(), {error}>",
-            input = std::any::type_name::<Self::Input>(),
-            error = std::any::type_name::<Self::Error>()
+            input = core::any::type_name::<Self::Input>(),
+            error = core::any::type_name::<Self::Error>()
         )
     }
 
@@ -430,7 +426,6 @@ impl<'a> Parser<'a> for SP {
 
 /// (a, (b,c)) -> (a,b,c)
 /// (a, (b, (c,d))) ->
-
 pub trait DeTuple {
     type Output;
     fn detuple(self) -> Self::Output;
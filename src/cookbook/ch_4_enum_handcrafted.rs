@@ -18,7 +18,8 @@ impl FromStr for Color {
             "Green" => Ok(Self::Green),
             _ => Err(ParsingError::NoMatch {
                 action: "matching color",
-                args: "",
+                args: "".into(),
+                offset: 0,
             }),
         }
     }
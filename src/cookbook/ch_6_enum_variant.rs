@@ -47,7 +47,8 @@ fn parse_number(s: &str) -> Result<(&str, Number), ParsingError> {
 
     Result::Err(ParsingError::NoMatch {
         action: "Unknown format",
-        args: "",
+        args: "".into(),
+        offset: 0,
     })
 }
 
@@ -70,7 +70,8 @@ pub fn parse_event(s: &str) -> Result<(&str, Event), ParsingError> {
         (Some(d), None) => Ok((c1, Event::DayOnly(d))),
         (None, None) => Result::Err(ParsingError::NoMatch {
             action: "Must specify day or time (or both)",
-            args: "",
+            args: "".into(),
+            offset: 0,
         }),
     }
 }
@@ -4,10 +4,7 @@ use crate::cookbook::{ch_2_simple_example::Time, ch_7_alternate::parse_clock};
 use crate::prelude::Cursor;
 use crate::prelude::*;
 
-///
-///
-///
-///
+/// eg "13:00-14:30"
 #[derive(Debug, PartialEq)]
 struct TimePeriod(Time, Time);
 
@@ -53,7 +50,10 @@ struct TrainTime {
 
 ///
 /// parse_with expects a closure/function that matches
-///  fn(Cursor) -> Result<(Cursor,T), dc::ParseError>
+///  fn(&str) -> Result<(&str, T), ParsingError>
+/// or
+///  fn(Cursor) -> Result<(Cursor, T), ParsingError>
+/// and appends the T to the tuple
 ///
 fn parse_traintime(c: &str) -> Result<(&str, TrainTime), ParsingError> {
     let (c, city, arr, dep) = Cursor::from(c)
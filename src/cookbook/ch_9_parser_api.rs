@@ -64,8 +64,7 @@ fn parse_str_money(s: &str) -> Result<(&str, Money), ParsingError> {
 ///
 /// the function takes a Cursor and returns a Result of (Cursor, T)
 ///
-
-// eg MoneyParser("$".to_string())
+/// eg MoneyParser("$".to_string())
 struct MoneyParser {
     currency: String,
 }
@@ -1,52 +1,158 @@
-use std::fmt;
+use alloc::rc::Rc;
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use core::ops::Deref;
 
 use crate::logging::Loggable;
 use crate::prelude::Matchable;
-use crate::{prelude::ParsingError, util};
+use crate::{error, prelude::ParsingError, util};
 
 #[derive(Debug, Clone)]
 pub struct Cursor<'a> {
     pub(crate) selection: Selection<'a>,
     pub(crate) cur: Option<&'a str>,
-    pub(crate) err: Option<ParsingError>,
-    pub(crate) context: &'static str,
+    pub(crate) err: Option<Failure>,
+    pub(crate) context: &'a str,
+    // named spans (name, start, end) set by mark/capture_to
+    pub(crate) names: SharedVec<(&'static str, &'a str, Option<&'a str>)>,
+    // errors skipped over by recover
+    pub(crate) errors: SharedVec<ParsingError>,
+    // matchers running out of input report Incomplete rather than NoMatch
+    pub(crate) partial: bool,
+    // the next NoMatch is made Fatal (see Matchable::commit)
+    pub(crate) committed: bool,
+    // the span named by debug_context, for errors (see Matchable::label)
+    pub(crate) label: &'static str,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// The span of input selected for get_selection/parse_selection. Each variant
+/// holds the input from the start of the selection (and optionally from its end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Selection<'a> {
+    /// nothing matched yet: selects from the start up to the cursor
     Defaulted(&'a str),
+    /// set by selection_start (and ended by selection_end)
     Start(&'a str, Option<&'a str>),
+    /// the most recent match
     Last(&'a str, &'a str),
 }
 
-// equal and error free
+/// Why a cursor is in error. A NoMatch from a matcher is usually backtracked over
+/// rather than reported, so it is held unformatted, without allocating, until the
+/// error is asked for
+#[derive(Debug, Clone)]
+pub(crate) enum Failure {
+    Error(ParsingError),
+    NoMatch {
+        action: &'static str,
+        args: InlineStr,
+        offset: usize,
+    },
+}
+
+/// A string too short to be worth allocating
+#[derive(Clone, Copy)]
+pub(crate) struct InlineStr {
+    len: u8,
+    buf: [u8; 22],
+}
+
+impl InlineStr {
+    /// None if s is too long
+    pub(crate) fn new(s: &str) -> Option<Self> {
+        let mut buf = [0; 22];
+        buf.get_mut(..s.len())?.copy_from_slice(s.as_bytes());
+        Some(Self {
+            len: s.len() as u8,
+            buf,
+        })
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for InlineStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A list shared by a cursor and its clones, copied when one of them changes it,
+/// so that cloning a cursor (eg when backtracking) does not allocate
+#[derive(Debug, Clone)]
+pub(crate) struct SharedVec<T>(Option<Rc<Vec<T>>>);
+
+impl<T: Clone> SharedVec<T> {
+    pub(crate) fn new() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn to_mut(&mut self) -> &mut Vec<T> {
+        Rc::make_mut(self.0.get_or_insert_with(Default::default))
+    }
+}
+
+impl<T> Deref for SharedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.0.as_deref().map_or(&[], |v| v)
+    }
+}
+
+impl<T: PartialEq> PartialEq for SharedVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+/// A saved cursor position (and selection), see [`Cursor::checkpoint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint<'a> {
+    cur: Option<&'a str>,
+    selection: Selection<'a>,
+    context: &'a str,
+}
+
+/// Compares every field: position, selection, named spans, recovered errors and
+/// the partial/committed flags and label. Cursors which failed are equal if their errors
+/// are (see ParsingError's PartialEq)
 impl<'a> PartialEq for Cursor<'a> {
-    #[allow(clippy::match_like_matches_macro)]
     fn eq(&self, other: &Self) -> bool {
         self.selection == other.selection
             && self.cur == other.cur
             && self.context == other.context
-            && match (&self.err, &other.err) {
-                (None, None) => true,
-                _ => false,
-            }
+            && self.names == other.names
+            && self.error() == other.error()
+            && self.errors == other.errors
+            && self.partial == other.partial
+            && self.committed == other.committed
+            && self.label == other.label
     }
 }
 
+/// equality is reflexive, errors included (see ParsingError's Eq)
+impl<'a> Eq for Cursor<'a> {}
+
 impl<'a> From<&'a str> for Cursor<'a> {
     #[inline]
     fn from(s: &'a str) -> Self {
-        let cur = Self {
-            selection: Selection::Defaulted(s),
-            cur: Some(s),
-            err: None,
-            context: "",
-        };
-        cur.log_success("Cursor::from", "");
-        cur
+        Self::with_context(s)
     }
 }
 
+impl<'a> From<&'a String> for Cursor<'a> {
+    #[inline]
+    fn from(s: &'a String) -> Self {
+        Self::with_context(s)
+    }
+}
 
 // impl<'a> From<&'a Self> for Cursor<'a> {
 //     #[inline]
@@ -63,8 +169,6 @@ impl<'a> From<&'a str> for Cursor<'a> {
 //     }
 // }
 
-
-
 impl<'a> TryFrom<Cursor<'a>> for &'a str {
     type Error = ParsingError;
 
@@ -81,6 +185,35 @@ impl<'a> TryFrom<Cursor<'a>> for &'a str {
 //     }
 // }
 
+/// eg "London |Arrive 11:20 PM| sel=|London|" with the cursor positioned
+/// at the first '|', and long text truncated
+impl<'a> fmt::Display for Cursor<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(cur) = self.cur else {
+            let e = self.error().map(|e| e.to_string()).unwrap_or_default();
+            return write!(
+                f,
+                "{} err={e}",
+                util::formatter_str(self.context).trim_end()
+            );
+        };
+        let consumed = &self.context[..self.byte_offset()];
+        let tail = consumed
+            .char_indices()
+            .rev()
+            .nth(19)
+            .map_or(consumed, |(i, _)| &consumed[i..]);
+        let (s, e) = self.selection.selection(cur);
+        write!(
+            f,
+            "{tail}{rest} sel={sel}",
+            tail = tail.escape_default(),
+            rest = util::formatter_str(cur).trim_end(),
+            sel = util::formatter_str(&s[..s.len() - e.len()]).trim_end(),
+        )
+    }
+}
+
 impl<'a> fmt::Display for Selection<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -102,7 +235,191 @@ impl<'a> fmt::Display for Selection<'a> {
     }
 }
 
+impl<'a> Cursor<'a> {
+    /// a cursor at the start of s, with s as the context against which offsets
+    /// and line/col are measured. The context cannot be changed after construction
+    #[inline]
+    pub fn with_context(s: &'a str) -> Self {
+        let cur = Self {
+            selection: Selection::Defaulted(s),
+            cur: Some(s),
+            err: None,
+            context: s,
+            names: SharedVec::new(),
+            errors: SharedVec::new(),
+            partial: false,
+            committed: false,
+            label: "",
+        };
+        cur.log_success("Cursor::from", "");
+        cur
+    }
+
+    /// a cursor over bytes (eg read from a file or socket), which must be valid UTF-8.
+    /// Invalid UTF-8 is a Fatal error
+    pub fn from_utf8(bytes: &'a [u8]) -> Result<Self, ParsingError> {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Ok(Self::with_context(s)),
+            Err(e) => Err(ParsingError::Fatal(Some(Box::new(e)))),
+        }
+    }
+
+    /// the matcher calls recorded on this thread since the last report, as an
+    /// indented tree grouped by debug_context. Taking the report clears it; calls
+    /// beyond a fixed limit are counted rather than recorded. Requires feature "debug-trace"
+    #[cfg(feature = "debug-trace")]
+    pub fn trace_report() -> String {
+        crate::trace::report()
+    }
+
+    /// saves the position and selection, for rewinding with restore
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            cur: self.cur,
+            selection: self.selection,
+            context: self.context,
+        }
+    }
+
+    /// rewinds to a checkpoint taken from this input, clearing any error set since
+    pub fn restore(mut self, cp: Checkpoint<'a>) -> Self {
+        debug_assert!(
+            core::ptr::eq(cp.context, self.context),
+            "checkpoint restored to a cursor on a different input"
+        );
+        self.selection = cp.selection;
+        match cp.cur {
+            Some(s) => {
+                self.cur = Some(s);
+                self.err = None;
+                self
+            }
+            // the checkpoint itself was taken in an error state
+            None => {
+                self.cur = None;
+                self.err = self
+                    .err
+                    .or_else(|| Some(Failure::Error(ParsingError::default())));
+                self
+            }
+        }
+    }
+
+    /// streaming mode: a matcher reaching the end of the input before its minimum
+    /// is met fails with ParsingError::Incomplete rather than NoMatch, eg digits(3..=3)
+    /// on "12" needs 1 more char. For parsing input arriving in chunks.
+    /// The mode belongs to the cursor (and its clones): a sub-parser taking a &str,
+    /// eg via parse_with, parse_struct_vec or a value parser, starts a fresh Cursor,
+    /// which is not partial unless the sub-parser makes it so
+    #[inline]
+    pub fn partial(mut self) -> Self {
+        self.partial = true;
+        self
+    }
+
+    /// the current selection, see Selection::span
+    #[inline]
+    pub fn selection(&self) -> &Selection<'a> {
+        &self.selection
+    }
+
+    /// the input not yet consumed (empty if the cursor is in an error state)
+    #[inline]
+    pub fn remaining(&self) -> &'a str {
+        self.cur.unwrap_or_default()
+    }
+
+    /// the number of bytes not yet consumed (0 if the cursor is in an error state)
+    #[inline]
+    pub fn remaining_len(&self) -> usize {
+        self.remaining().len()
+    }
+
+    /// the full input the cursor was created from (see with_context)
+    #[inline]
+    pub fn input(&self) -> &'a str {
+        self.context
+    }
+
+    /// a cursor per line of the remaining input (without its "\n" or "\r\n"), including a
+    /// last line with no terminator. Offsets and line_col of each line cursor are measured
+    /// against the original input, though its input() then ends where the line does.
+    /// A cursor in error yields just itself, so that its error is not lost
+    pub fn lines(self) -> impl Iterator<Item = Cursor<'a>> {
+        let context = self.context;
+        let mut start = self.cur.map(|s| context.len() - s.len());
+        let mut failed = self.cur.is_none().then(|| self.clone());
+        core::iter::from_fn(move || {
+            if let Some(c) = failed.take() {
+                return Some(c);
+            }
+            let from = start.filter(|&i| i < context.len())?;
+            let rest = &context[from..];
+            let (len, next) = match rest.find('\n') {
+                Some(i) => (i, from + i + 1),
+                None => (rest.len(), context.len()),
+            };
+            let line = &rest[..len];
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            start = Some(next);
+            let end = from + line.len();
+            Some(Cursor {
+                selection: Selection::Defaulted(&context[from..end]),
+                cur: Some(&context[from..end]),
+                err: None,
+                context: &context[..end],
+                names: SharedVec::new(),
+                errors: SharedVec::new(),
+                partial: self.partial,
+                committed: false,
+                label: self.label,
+            })
+        })
+    }
+
+    /// the error the cursor is in, if any
+    pub(crate) fn error(&self) -> Option<ParsingError> {
+        match self.err.as_ref()? {
+            Failure::Error(e) => Some(e.clone()),
+            Failure::NoMatch {
+                action,
+                args,
+                offset,
+            } => Some(ParsingError::NoMatch {
+                action,
+                args: error::with_found(args.as_str(), &self.context[*offset..]).into(),
+                offset: *offset,
+            }),
+        }
+    }
+
+    /// byte offset of the cursor within the original input
+    /// (the full input length, once in an error state)
+    #[inline]
+    pub(crate) fn byte_offset(&self) -> usize {
+        self.context.len() - self.cur.map(str::len).unwrap_or(0)
+    }
+
+    /// 1-based line and column (in chars) of the cursor within the original input
+    pub(crate) fn line_and_col(&self) -> (usize, usize) {
+        let consumed = &self.context[..self.byte_offset()];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let col = consumed[line_start..].chars().count() + 1;
+        (line, col)
+    }
+
+    /// the column of line_and_col, scanning back only as far as the start of the line
+    pub(crate) fn col(&self) -> usize {
+        let consumed = &self.context[..self.byte_offset()];
+        let line_start = consumed.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        consumed[line_start..].chars().count() + 1
+    }
+}
+
 impl<'a> Selection<'a> {
+    /// the input from the start of the selection onwards
     pub fn start(&self) -> &'a str {
         match self {
             Selection::Defaulted(s) => s,
@@ -126,6 +443,13 @@ impl<'a> Selection<'a> {
             Selection::Last(s, e) => (s, e),
         }
     }
+
+    /// the selected text, given the remaining input at the cursor. None if cur can't
+    /// be the remaining input of the cursor the selection came from
+    pub fn span(&self, cur: &'a str) -> Option<&'a str> {
+        let (s, e) = self.selection(cur);
+        s.get(..s.len().checked_sub(e.len())?)
+    }
 }
 
 #[cfg(test)]
@@ -144,6 +468,129 @@ mod tests {
 
         let s2: &str = c2.try_into().unwrap();
         assert_eq!(s2, s);
+    }
+
+    #[test]
+    fn test_display() {
+        let c = Cursor::from("London Arrive 11:20 PM")
+            .selection_start()
+            .word()
+            .selection_end()
+            .ws();
+        assert_eq!(c.to_string(), "London |Arrive 11:20 PM| sel=|London|");
+
+        let c = Cursor::from("London Arrive")
+            .word()
+            .parse_selection::<String>()
+            .0;
+        assert_eq!(c.to_string(), "London| Arrive| sel=|London|");
+
+        let c = Cursor::from("héllo wörld").text("hé");
+        assert_eq!(c.to_string(), "h\\u{e9}|llo w\\u{f6}rld| sel=|h\\u{e9}|");
+
+        let c = Cursor::from("abc").text("X");
+        assert!(c.to_string().starts_with("|abc| err=FailedMatch"), "{c}");
+
+        let long = "x".repeat(100);
+        let c = Cursor::from(long.as_str()).text("xx");
+        assert_eq!(c.to_string().len(), "xx".len() + 35 + " sel=|xx|".len());
+    }
+
+    #[test]
+    fn test_with_context() {
+        let c = Cursor::with_context("line 1\nline 2")
+            .scan_eol()
+            .text("line");
+        assert_eq!(c.remaining(), " 2");
+        assert_eq!(c.offset(), 11);
+        assert_eq!(c.line_col(), (2, 5));
+        assert_eq!(c.text("x").remaining(), "");
+        assert_eq!(Cursor::from("abc"), Cursor::with_context("abc"));
+    }
+
+    #[test]
+    fn test_lines() {
+        let c = Cursor::from("# hdr\na=1\r\n\nb=22");
+        let lines: Vec<_> = c.text("# ").lines().collect();
+        let text: Vec<_> = lines.iter().map(|c| c.remaining()).collect();
+        assert_eq!(text, ["hdr", "a=1", "", "b=22"]);
+        let c = lines[3].clone().text("b=").digits(1..);
+        assert_eq!(c.line_col(), (4, 5));
+        assert_eq!(c.offset(), 16);
+        assert!(lines[1]
+            .clone()
+            .text("a=1")
+            .end_of_stream()
+            .validate()
+            .is_ok());
+        assert_eq!(Cursor::from("x\n").lines().count(), 1);
+        assert_eq!(Cursor::from("").lines().count(), 0);
+        let mut lines = Cursor::from("x").text("y").lines();
+        assert!(lines.next().unwrap().validate().is_err());
+        assert!(lines.next().is_none());
+    }
 
+    #[test]
+    fn test_from_string_and_utf8() {
+        let owned = String::from("ab cd");
+        assert_eq!(Cursor::from(&owned).word().str().unwrap(), " cd");
+        let c = Cursor::from_utf8("héllo".as_bytes()).unwrap();
+        assert_eq!(c.text("hé").str().unwrap(), "llo");
+        let e = Cursor::from_utf8(&[b'a', 0xff]).unwrap_err();
+        assert!(matches!(e, ParsingError::Fatal(Some(_))), "{e:?}");
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let c = Cursor::from("abc def").word();
+        let cp = c.checkpoint();
+        let c = c.ws().text("xyz");
+        assert!(c.str().is_err());
+        let c = c.restore(cp);
+        assert_eq!(c.str().unwrap(), " def");
+        assert_eq!(c.get_selection().unwrap(), "abc");
+
+        let c = c.ws().text("def").restore(cp).ws().word();
+        assert_eq!(c.get_selection().unwrap(), "def");
+        assert_eq!(c.offset(), 7);
+    }
+
+    #[test]
+    fn test_selection() {
+        let c = Cursor::from("ab cd");
+        assert_eq!(c.selection().span(c.remaining()), Some(""));
+        let c = c.word();
+        assert_eq!(c.selection(), &Selection::Last("ab cd", " cd"));
+        assert_eq!(c.selection().span(c.remaining()), Some("ab"));
+        assert_eq!(c.selection().start(), "ab cd");
+        let c = c.selection_start().ws().word();
+        assert_eq!(c.selection().span(c.remaining()), Some(" cd"));
+
+        // a cursor from elsewhere
+        assert_eq!(Selection::Defaulted("ab").span("abcdef"), None);
+    }
+
+    #[test]
+    fn test_error_cursor_eq() {
+        let c = Cursor::from("abc");
+        assert_eq!(c.clone().text("x"), c.clone().text("x"));
+        assert_ne!(c.clone().text("x"), c.clone().text("y"));
+        assert_ne!(c.clone().text("x"), c.clone());
+        let fatal = |c: Cursor<'static>| c.set_error(ParsingError::Fatal(None));
+        assert_eq!(fatal(c.clone()), fatal(c.clone()));
+        assert_ne!(fatal(c.clone()), c.clone().text("x"));
+
+        fn assert_eq_impl<T: Eq>(_: &T) {}
+        assert_eq_impl(&c);
+    }
+
+    #[test]
+    fn test_input() {
+        let c = Cursor::from("abc def").word();
+        assert_eq!(c.input(), "abc def");
+        assert_eq!(c.remaining_len(), 4);
+        let c = c.text("x");
+        assert_eq!(c.input(), "abc def");
+        assert_eq!(c.remaining_len(), 0);
     }
 }
@@ -1,10 +1,28 @@
-use std::{
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+use core::{
+    any::TypeId,
     convert::Infallible,
-    error::Error,
-    fmt, matches,
-    num::{ParseFloatError, ParseIntError},
+    fmt,
+    marker::PhantomData,
+    matches,
+    num::{IntErrorKind, ParseFloatError, ParseIntError},
     str::ParseBoolError,
 };
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// the cause carried by a Fatal error. Without the `std` feature there is no
+/// `std::error::Error`, so any error that can be debugged and displayed is carried
+#[cfg(not(feature = "std"))]
+pub trait Error: fmt::Debug + fmt::Display {}
+
+#[cfg(not(feature = "std"))]
+impl<E: fmt::Debug + fmt::Display + ?Sized> Error for E {}
 
 /// Indicates whether an error can be recovered from, and parsing can continue.
 /// Errors such as "config file not found" in parse functions are likely fatal and
@@ -13,14 +31,27 @@ pub trait Recoverable {
     fn is_recoverable(&self) -> bool;
 }
 
+/// Non-exhaustive, so that further kinds of error (as Incomplete was) can be added
+/// without a breaking change
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ParsingError {
     Fatal(Option<Box<dyn Error>>),
     NoMatch {
         action: &'static str,
-        args: &'static str,
+        args: Cow<'static, str>,
+        /// byte offset into the input at which the cursor failed to match
+        offset: usize,
+    },
+    /// the input ended before a matcher was satisfied, on a partial cursor
+    /// (see Cursor::partial). More input may allow the match to succeed
+    Incomplete {
+        /// the minimum number of further chars (or bytes, for bytes()) needed
+        needed: usize,
     },
 }
+/// Incomplete is not recoverable, so that alternatives are not tried until more
+/// input is available
 impl Recoverable for ParsingError {
     fn is_recoverable(&self) -> bool {
         matches!(self, Self::NoMatch { .. })
@@ -31,16 +62,28 @@ impl Default for ParsingError {
     fn default() -> Self {
         Self::NoMatch {
             action: "",
-            args: "",
+            args: "".into(),
+            offset: 0,
         }
     }
 }
 
+/// overflow is fatal (the digits matched, but the number is out of range),
+/// whereas "not a number here" is a recoverable NoMatch
 impl From<ParseIntError> for ParsingError {
-    fn from(_value: ParseIntError) -> Self {
+    fn from(value: ParseIntError) -> Self {
+        let args = match value.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                return ParsingError::Fatal(Some(Box::new(value)))
+            }
+            IntErrorKind::Empty => "empty",
+            IntErrorKind::InvalidDigit => "invalid digit",
+            _ => "",
+        };
         ParsingError::NoMatch {
             action: "parse int error",
-            args: "",
+            args: args.into(),
+            offset: 0,
         }
     }
 }
@@ -49,16 +92,68 @@ impl From<ParseFloatError> for ParsingError {
     fn from(_value: ParseFloatError) -> Self {
         ParsingError::NoMatch {
             action: "parse float error",
-            args: "",
+            args: "invalid float".into(),
+            offset: 0,
+        }
+    }
+}
+
+// the TypeId of T, without the 'static bound of TypeId::of, so that parse_selection
+// keeps accepting any FromStr. Types differing only in lifetimes share an id, which is
+// no loss when comparing with the numeric primitives
+fn type_id<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
         }
     }
+
+    let phantom = PhantomData::<T>;
+    let any: &dyn NonStaticAny = &phantom;
+    // SAFETY: only the trait object's lifetime bound is extended, to call type_id, which
+    // reads no data. Lifetimes are erased by code generation, so the id is that of T
+    let any: &(dyn NonStaticAny + 'static) = unsafe { core::mem::transmute(any) };
+    any.type_id()
+}
+
+/// the error for text which T's FromStr rejected. The error type of a FromStr impl
+/// is opaque, so for the numeric primitives (told apart by TypeId) the text is
+/// re-parsed to recover the cause (and overflow is fatal, as above)
+pub(crate) fn from_str_failure<T>(text: &str) -> ParsingError {
+    macro_rules! reparse {
+        ($($t:ty),*) => {
+            $(
+                if type_id::<T>() == TypeId::of::<$t>() {
+                    if let Err(e) = text.parse::<$t>() {
+                        return e.into();
+                    }
+                }
+            )*
+        };
+    }
+    reparse!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+    ParsingError::NoMatch {
+        action: "FromStr",
+        args: "".into(),
+        offset: 0,
+    }
 }
 
 impl From<ParseBoolError> for ParsingError {
     fn from(_value: ParseBoolError) -> Self {
         ParsingError::NoMatch {
             action: "parse bool error",
-            args: "",
+            args: "".into(),
+            offset: 0,
         }
     }
 }
@@ -73,15 +168,94 @@ impl Clone for ParsingError {
     #[inline]
     fn clone(&self) -> Self {
         match self {
-            Self::Fatal(_e) => Self::Fatal(None),
-            Self::NoMatch { action, args } => Self::NoMatch { action, args },
+            // the source can't be cloned, but its message is kept
+            #[cfg(feature = "std")]
+            Self::Fatal(e) => Self::Fatal(e.as_ref().map(|e| e.to_string().into())),
+            #[cfg(not(feature = "std"))]
+            Self::Fatal(e) => Self::Fatal(
+                e.as_ref()
+                    .map(|e| Box::new(e.to_string()) as Box<dyn Error>),
+            ),
+            Self::NoMatch {
+                action,
+                args,
+                offset,
+            } => Self::NoMatch {
+                action,
+                args: args.clone(),
+                offset: *offset,
+            },
+            Self::Incomplete { needed } => Self::Incomplete { needed: *needed },
         }
     }
 }
 
+/// NoMatch errors are equal if their action, args and offset are. Any two Fatal
+/// errors are equal, as their sources can't be compared
+impl PartialEq for ParsingError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Fatal(_), Self::Fatal(_)) => true,
+            (
+                Self::NoMatch {
+                    action,
+                    args,
+                    offset,
+                },
+                Self::NoMatch {
+                    action: a2,
+                    args: args2,
+                    offset: o2,
+                },
+            ) => action == a2 && args == args2 && offset == o2,
+            (Self::Incomplete { needed }, Self::Incomplete { needed: n2 }) => needed == n2,
+            _ => false,
+        }
+    }
+}
+
+/// Fatal equalling Fatal keeps equality reflexive
+impl Eq for ParsingError {}
+
+/// a NoMatch recording the args (eg the expected word or offending text)
+#[inline]
+pub fn failure(action: &'static str, args: &str) -> ParsingError {
+    ParsingError::NoMatch {
+        action,
+        args: match args {
+            "" => Cow::Borrowed(""),
+            args => Cow::Owned(args.to_string()),
+        },
+        offset: 0,
+    }
+}
+
+/// the args of a NoMatch from a matcher, followed by the text found instead
+/// (up to the end of its line), eg `xy (found "ab cd")`
+pub(crate) fn with_found(args: &str, rest: &str) -> String {
+    let found: String = rest
+        .chars()
+        .enumerate()
+        .take_while(|&(i, c)| i == 0 || c != '\n')
+        .take(12)
+        .map(|(_, c)| c)
+        .collect();
+    match (args, found.as_str()) {
+        ("", "") => "found end of input".to_string(),
+        ("", found) => format!("found {found:?}"),
+        (args, "") => format!("{args} (found end of input)"),
+        (args, found) => format!("{args} (found {found:?})"),
+    }
+}
+
+/// a NoMatch without args, avoiding any allocation
 #[inline]
-pub fn failure(action: &'static str, _args: &str) -> ParsingError {
-    ParsingError::NoMatch { action, args: "" }
+pub fn failure_static(action: &'static str) -> ParsingError {
+    ParsingError::NoMatch {
+        action,
+        args: Cow::Borrowed(""),
+        offset: 0,
+    }
 }
 
 impl fmt::Display for ParsingError {
@@ -92,11 +266,64 @@ impl fmt::Display for ParsingError {
                 "Fatal:{msg}",
                 msg = e.as_ref().map(|e| e.to_string()).unwrap_or_default()
             )?,
-            Self::NoMatch { action, args } => {
-                write!(f, "FailedMatch: (action='{action}' args='{args}')")?
-            }
+            Self::NoMatch {
+                action,
+                args,
+                offset,
+            } => write!(
+                f,
+                "FailedMatch: (action='{action}' args='{args}') at byte {offset}"
+            )?,
+            Self::Incomplete { needed } => write!(f, "Incomplete: {needed} more needed")?,
         };
         Ok(())
     }
 }
-impl std::error::Error for ParsingError {}
+/// The source of a Fatal error is the boxed error it carries. Note that cloning a
+/// fatal error keeps only the message of its source, not the original error
+#[cfg(feature = "std")]
+impl std::error::Error for ParsingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Fatal(Some(e)) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl ParsingError {
+    /// records the byte offset at which a NoMatch occurred, keeping the furthest
+    /// offset if the error was already located (eg by a cloned sub-cursor)
+    #[inline]
+    pub(crate) fn at(self, at: usize) -> Self {
+        match self {
+            Self::NoMatch {
+                action,
+                args,
+                offset,
+            } => Self::NoMatch {
+                action,
+                args,
+                offset: offset.max(at),
+            },
+            e => e,
+        }
+    }
+
+    /// prefixes the args of a NoMatch with "while parsing `<label>`: ", so that
+    /// nested annotations read outermost first
+    pub(crate) fn in_context(self, label: &'static str) -> Self {
+        match self {
+            Self::NoMatch {
+                action,
+                args,
+                offset,
+            } => Self::NoMatch {
+                action,
+                args: format!("while parsing <{label}>: {args}").into(),
+                offset,
+            },
+            e => e,
+        }
+    }
+}
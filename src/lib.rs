@@ -1,4 +1,5 @@
 // #![cfg_attr(debug_assertions, allow(dead_code))]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(dead_code)]
 #![warn(clippy::all)]
 #![warn(clippy::correctness)]
@@ -10,10 +11,12 @@
 #![allow(clippy::bool_assert_comparison)]
 
 
+extern crate alloc;
 
+#[cfg(feature = "std")]
 use std::cell::Cell;
 
-
+#[cfg(feature = "std")]
 mod contrib;
 mod logging;
 mod parser;
@@ -22,13 +25,65 @@ mod error;
 mod text_parser;
 mod util;
 mod combo;
+#[cfg(feature = "debug-trace")]
+mod trace;
 
 pub mod prelude;
 
+#[doc(hidden)]
+pub use crate::parser::cursor_fn as __cursor_fn;
+
+// lets the derive's generated ::daisychain paths resolve within this crate's own tests
+#[cfg(feature = "derive")]
+extern crate self as daisychain;
+
+/// support for the code generated by derive(Parse)
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __derive {
+    use crate::prelude::{Cursor, Matchable, ParsingError};
+
+    pub fn check(c: Cursor<'_>) -> Result<Cursor<'_>, ParsingError> {
+        match c.is_skip() {
+            false => Ok(c),
+            true => Err(c.validate().err().unwrap_or(ParsingError::Fatal(None))),
+        }
+    }
+
+    pub fn take<T>((c, t): (Cursor<'_>, Option<T>)) -> Result<(Cursor<'_>, T), ParsingError> {
+        let c = check(c)?;
+        match t {
+            Some(t) => Ok((c, t)),
+            None => Err(c.validate().err().unwrap_or(ParsingError::Fatal(None))),
+        }
+    }
+}
+
 #[cfg(any(feature="cookbook", doc))] 
 pub mod cookbook;
 
 pub(crate) const LOG_TARGET: &str = "dc"; // env!("CARGO_PKG_NAME");
 
-thread_local!(pub(crate) static LABEL: Cell<&'static str> = Cell::new(""));
+#[cfg(feature = "std")]
+thread_local!(static LABEL: Cell<&'static str> = const { Cell::new("") });
+
+// the span name shown in log lines. It is held in a thread local, so without std
+// there is none, and setting it does nothing
+#[cfg(feature = "std")]
+pub(crate) fn log_label() -> &'static str {
+    LABEL.with(|f| f.get())
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn set_log_label(label: &'static str) {
+    LABEL.with(|f| f.set(label));
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn log_label() -> &'static str {
+    ""
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn set_log_label(_label: &'static str) {}
 
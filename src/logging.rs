@@ -2,8 +2,9 @@ use log::Level::Trace;
 use log::{log_enabled, trace};
 
 use crate::prelude::Matchable;
-use crate::{util, LABEL, LOG_TARGET};
-use std::fmt::Debug;
+use crate::{util, LOG_TARGET};
+use alloc::format;
+use core::fmt::Debug;
 
 pub(crate) trait Loggable {
     const LABEL_WIDTH: usize = 15;
@@ -19,47 +20,61 @@ where
     Cur: Matchable<'a>,
 {
     fn log_inputs<Args: Debug>(&self, msg: &str, args: Args) {
+        #[cfg(feature = "debug-trace")]
+        crate::trace::enter(msg, &args, self.str().ok());
         if log_enabled!(target: LOG_TARGET, Trace) && self.is_skip() {
             trace!(
                 target: LOG_TARGET,
                 "{inp:<iw$} {label:<lw$} : {operation:<lw$}",
                 iw = Self::INPUT_WIDTH,
                 lw = Self::LABEL_WIDTH,
-                label = LABEL.with(|f| f.get()),
+                label = crate::log_label(),
                 inp = util::formatter_str(self.str().unwrap_or_default()),
                 operation = format!("{msg}({args:?})"),
             );
         }
     }
     fn log_success<Args: Debug>(&self, msg: &str, args: Args) {
+        #[cfg(feature = "debug-trace")]
+        crate::trace::exit(
+            msg,
+            &args,
+            util::formatter_str(self.str().unwrap_or_default())
+                .trim_end()
+                .into(),
+        );
         trace!(
             target: LOG_TARGET,
             "{inp:<iw$} {label:<lw$} : {operation:<lw$}",
             iw = Self::INPUT_WIDTH,
             lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
+            label = crate::log_label(),
             inp = util::formatter_str(self.str().unwrap_or_default()),
             operation = format!("{msg}({args:?})"),
         );
     }
     fn log_success_with_result<A1: Debug, A2: Debug>(&self, msg: &str, args: A1, res: A2) {
+        #[cfg(feature = "debug-trace")]
+        crate::trace::exit(msg, &args, format!("{res:?}"));
         trace!(
             target: LOG_TARGET,
             "{inp:<iw$} {label:<lw$} : {operation:<lw$} -> {res:?}",
             iw = Self::INPUT_WIDTH,
             lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
+            label = crate::log_label(),
             inp = util::formatter_str(self.str().unwrap_or_default()),
             operation = format!("{msg}:{args:?}"),
         );
     }
     fn log_failure<Args: Debug, Error: Debug>(&self, msg: &str, args: Args, error: &Error) {
+        #[cfg(feature = "debug-trace")]
+        crate::trace::exit(msg, &args, format!("{error:?}"));
         trace!(
             target: LOG_TARGET,
             "{inp:<iw$} {label:<lw$} : {operation:<lw$} -> {e:?}",
             iw = Self::INPUT_WIDTH,
             lw = Self::LABEL_WIDTH,
-            label = LABEL.with(|f| f.get()),
+            label = crate::log_label(),
             inp = util::formatter_str(self.str().unwrap_or_default()),
             operation = format!("{msg}({args:?})"),
             e = error,
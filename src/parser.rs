@@ -36,9 +36,87 @@ pub trait Parser<'c, C, T> {
 //     };
 // }
 
+/// Types which know how to parse themselves from a cursor, for use with
+/// [`Matchable::parse_as`]. Unlike FromStr, the parser can consume a multi-field
+/// struct from the input, leaving the cursor after it
+pub trait Parse<'c>: Sized {
+    fn parse(c: Cursor<'c>) -> Result<(Cursor<'c>, Self), ParsingError>;
+}
+
+/// Runs the parser over the whole of input, failing if any input is left
+/// unconsumed (the error's offset and args locate the leftover text)
+pub fn parse_all<'a, P, C, T>(input: &'a str, parser: P) -> Result<T, ParsingError>
+where
+    P: Parser<'a, C, T, Error = ParsingError>,
+    Cursor<'a>: TryInto<C> + From<C>,
+    C: TryInto<&'a str>,
+{
+    let (rest, t) = Cursor::with_context(input).parse_with(parser).validate()?;
+    if !rest.is_empty() {
+        return Err(ParsingError::NoMatch {
+            action: "parse_all",
+            args: crate::util::unconsumed(rest).into(),
+            offset: input.len() - rest.len(),
+        });
+    }
+    Ok(t)
+}
+
 pub type StrFunc<T, E> = for<'c> fn(&'c str) -> Result<(&'c str, T), E>;
 pub type StrMethod<T, X> = for<'c> fn(x: &'c X, &'c str) -> Result<(&'c str, T), ParsingError>;
 
+/// A method chain on a Cursor as a plain fn value, see [`parser!`](crate::parser!).
+/// Being a fn pointer it can be named, stored in a struct field and reused
+pub type CursorFn<T> = for<'c> fn(Cursor<'c>) -> Result<(Cursor<'c>, T), ParsingError>;
+
+/// The outcome of a method chain, as the Result expected of a Parser
+pub trait Parsed<'c, T> {
+    fn into_result(self) -> Result<(Cursor<'c>, T), ParsingError>;
+}
+
+impl<'c, T> Parsed<'c, T> for (Cursor<'c>, Option<T>) {
+    fn into_result(self) -> Result<(Cursor<'c>, T), ParsingError> {
+        match self {
+            (c, Some(t)) if !c.is_skip() => Ok((c, t)),
+            (c, _) => Err(c
+                .validate()
+                .err()
+                .unwrap_or_else(|| crate::error::failure_static("parser"))),
+        }
+    }
+}
+
+/// pins the closure's signature to one that is generic over the cursor lifetime
+#[doc(hidden)]
+pub fn cursor_fn<T, F>(f: F) -> F
+where
+    F: for<'c> Fn(Cursor<'c>) -> Result<(Cursor<'c>, T), ParsingError>,
+{
+    f
+}
+
+/// Turns a method chain yielding a value into a reusable parser
+///
+/// ```
+/// use daisychain::prelude::*;
+/// use daisychain::parser;
+///
+/// struct Grammar {
+///     int: CursorFn<u32>,
+/// }
+/// let g = Grammar {
+///     int: parser!(|c| c.digits(1..).parse_selection::<u32>().ws()),
+/// };
+/// let (rest, v) = Cursor::from("1 2 3").many0(g.int).validate().unwrap();
+/// assert_eq!((rest, v), ("", vec![1, 2, 3]));
+/// ```
+#[macro_export]
+macro_rules! parser {
+    (|$c:ident| $body:expr) => {
+        $crate::__cursor_fn(|$c| $crate::prelude::Parsed::into_result($body))
+    };
+}
+
 impl<'c, T> Parser<'c, Cursor<'c>, T> for StrFunc<T, ParsingError> {
     type Error = ParsingError;
     fn parse(&mut self, c: Cursor<'c>) -> Result<(Cursor<'c>, T), ParsingError> {
@@ -72,9 +150,13 @@ impl<'c, T, X> Parser<'c, Cursor<'c>, T> for (&'c X, StrMethod<T, X>) {
 mod tests {
     use crate::{
         parser::{Parser, StrFunc, StrMethod},
-        prelude::{Cursor, ParsingError},
+        prelude::{Cursor, Matchable, ParsingError, Selectable},
     };
 
+    fn int(s: &str) -> Result<(&str, i32), ParsingError> {
+        Cursor::from(s).digits(1..).parse_selection().validate()
+    }
+
     #[test]
     fn test_casting() {
         // define a simple lexer+parser
@@ -113,4 +195,78 @@ mod tests {
         lp("|c| self.parse_ex4(c)".into(), |c| selfie.parse_ex4(c));
         lp("|c| parse_ex(c)      ".into(), |c| parse_ex3(c));
     }
+
+    #[test]
+    fn test_parse_all() {
+        use crate::parser::parse_all;
+
+        assert_eq!(parse_all("123", int).unwrap(), 123);
+        let e = parse_all("12ab", int).unwrap_err();
+        assert!(
+            matches!(e, ParsingError::NoMatch { action: "parse_all", offset: 2, ref args } if args.contains("ab")),
+            "{e}"
+        );
+        assert!(parse_all("x", int).is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_parse() {
+        use crate::prelude::{Matchable, Parse};
+
+        #[derive(Parse, Debug, PartialEq)]
+        #[daisychain(digits(2), lit(":"), digits(2), ws, word)]
+        struct Time {
+            hours: u8,
+            mins: u8,
+            zone: String,
+        }
+
+        let (c, t) = Time::parse(Cursor::from("12:30 UTC!")).unwrap();
+        assert_eq!(c.str().unwrap(), "!");
+        assert_eq!(t.hours, 12);
+        assert_eq!(t.mins, 30);
+        assert_eq!(t.zone, "UTC");
+        assert!(Time::parse(Cursor::from("12-30 UTC")).is_err());
+        assert!(Time::parse(Cursor::from("12:3")).is_err());
+    }
+
+    #[test]
+    fn test_parser_macro() {
+        use crate::parser::CursorFn;
+        use crate::prelude::{Matchable, Selectable};
+
+        struct Grammar {
+            key: CursorFn<String>,
+            value: CursorFn<i32>,
+        }
+        let g = Grammar {
+            key: parser!(|c| c.word().parse_selection::<String>().text("=")),
+            value: parser!(|c| c.int(1..).parse_selection()),
+        };
+        let (rest, k, v) = Cursor::from("x=-12;")
+            .parse_with(g.key)
+            .parse_with(g.value)
+            .validate()
+            .unwrap();
+        assert_eq!((rest, k.as_str(), v), (";", "x", -12));
+        assert!(Cursor::from("x-12").parse_with(g.key).validate().is_err());
+    }
+
+    #[test]
+    fn test_prelude_parser_bound() {
+        use crate::prelude::*;
+
+        // a helper generic over any parser, with the bound Matchable's methods use
+        fn pair<'a, P>(s: &'a str, mut p: P) -> Result<(&'a str, (i32, i32)), ParsingError>
+        where
+            P: Parser<'a, &'a str, i32, Error = ParsingError>,
+        {
+            let (s, a) = p.parse(s)?;
+            let (s, b) = p.parse(Cursor::from(s).text(",").validate()?)?;
+            Ok((s, (a, b)))
+        }
+        assert_eq!(pair("3,4;", int).unwrap(), (";", (3, 4)));
+        assert!(pair("3;4", int).is_err());
+    }
 }
@@ -1,5 +1,8 @@
-pub use crate::text_parser::{Bind, Matchable, Selectable};
-pub use crate::cursor::Cursor;
+pub use crate::text_parser::{AndThen, Bind, FromStrRadix, MapValue, MappedSelection, Matchable, Number, Selectable, StructIter, Trailing};
+pub use crate::cursor::{Checkpoint, Cursor, Selection};
+pub use crate::parser::{parse_all, CursorFn, Parse, Parsed, Parser};
+#[cfg(feature = "derive")]
+pub use daisychain_derive::Parse;
 pub use crate::error::ParsingError;
 
 pub mod lazy {
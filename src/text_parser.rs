@@ -1,4 +1,5 @@
-use std::{
+use alloc::{borrow::Cow, boxed::Box, format, string::String, vec, vec::Vec};
+use core::{
     fmt::Debug,
     ops::{Bound, RangeBounds},
     str::FromStr,
@@ -8,11 +9,12 @@ use log::log_enabled;
 use log::Level::Trace;
 
 use crate::{
-    cursor::Selection,
-    error,
+    cursor::{Failure, InlineStr, Selection},
+    error::{self, Recoverable},
+    log_label,
     logging::Loggable,
     prelude::{Cursor, ParsingError},
-    LABEL, LOG_TARGET,
+    set_log_label, LOG_TARGET,
 };
 
 fn cursorify<'a, T>(
@@ -53,6 +55,96 @@ fn cursorify<'a, T>(
 //     }
 // }
 
+/// A selection transformed by [`Selectable::map_selection`], awaiting parsing.
+/// The cursor itself is unaffected by the mapping.
+#[derive(Debug)]
+pub struct MappedSelection<'a, C> {
+    cur: C,
+    mapped: Option<Cow<'a, str>>,
+}
+
+impl<'a, C: Selectable<'a>> MappedSelection<'a, C> {
+    /// parses the mapped selection, returning the cursor as positioned before the mapping
+    pub fn parse_selection<T: FromStr + Debug>(self) -> (C, Option<T>) {
+        let Some(text) = self.mapped else {
+            return (self.cur, None);
+        };
+        match text.parse::<T>() {
+            Ok(t) => {
+                self.cur.log_success_with_result(
+                    "----> map_selection",
+                    core::any::type_name::<T>(),
+                    &t,
+                );
+                (self.cur, Some(t))
+            }
+            Err(_) => {
+                let e = labelled(self.cur.label(), error::from_str_failure::<T>(&text));
+                self.cur.log_failure("parse_selection", "", &e);
+                (self.cur.set_error(e), None)
+            }
+        }
+    }
+}
+
+/// Lazily parses repeated structs, see [`Matchable::iter_struct`]
+pub struct StructIter<'a, P> {
+    cur: Option<&'a str>,
+    parser: P,
+    // the error of a cursor already failed when iteration began, yielded first
+    err: Option<ParsingError>,
+    // set by a NoMatch, which ends iteration leaving cur where it stopped
+    done: bool,
+}
+
+impl<'a, P> StructIter<'a, P> {
+    /// the input not yet parsed, or None once iteration has ended with an error
+    pub fn remaining(&self) -> Option<&'a str> {
+        self.cur
+    }
+}
+
+impl<'a, P, T> Iterator for StructIter<'a, P>
+where
+    P: FnMut(&'a str) -> Result<(&'a str, T), ParsingError>,
+{
+    type Item = Result<T, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.err.take() {
+            return Some(Err(e));
+        }
+        if self.done {
+            return None;
+        }
+        let s = self.cur?;
+        match (self.parser)(s) {
+            // an item consuming nothing would repeat forever, so it is the last
+            Ok((rest, t)) if rest.len() == s.len() => {
+                self.cur = None;
+                Some(Ok(t))
+            }
+            Ok((rest, t)) => {
+                self.cur = Some(rest);
+                Some(Ok(t))
+            }
+            Err(ParsingError::NoMatch { .. }) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.cur = None;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a, P, T> core::iter::FusedIterator for StructIter<'a, P> where
+    P: FnMut(&'a str) -> Result<(&'a str, T), ParsingError>
+{
+}
+
 pub trait Bind<T> {
     type Output;
     fn bind(self, t: &mut T) -> Self::Output;
@@ -74,6 +166,117 @@ where
     }
 }
 
+/// continue parsing with access to the value just parsed, eg a length prefix
+/// deciding how many items follow. The closure is not called if the value is missing
+pub trait AndThen<T>: Sized {
+    type Cursor;
+    fn and_then<F, U>(self, f: F) -> (Self, Option<U>)
+    where
+        F: FnOnce(&T, Self::Cursor) -> Result<(Self::Cursor, U), ParsingError>;
+}
+
+impl<'a, C, T> AndThen<T> for (C, Option<T>)
+where
+    C: Matchable<'a> + Clone,
+{
+    type Cursor = C;
+
+    fn and_then<F, U>(self, f: F) -> (Self, Option<U>)
+    where
+        F: FnOnce(&T, C) -> Result<(C, U), ParsingError>,
+    {
+        let (c, opt_t) = self;
+        let Some(t) = opt_t else {
+            return ((c, None), None);
+        };
+        if c.is_skip() {
+            return ((c, Some(t)), None);
+        }
+        match f(&t, c.clone()) {
+            Ok((c, _)) if c.is_skip() => ((c, Some(t)), None),
+            Ok((c, u)) => ((c, Some(t)), Some(u)),
+            Err(e) => ((c.set_error(e), Some(t)), None),
+        }
+    }
+}
+
+/// converts the value just parsed while keeping the cursor, eg an i32 hour stored as u8.
+/// Available however deeply the (cursor, value) tuples are nested, as it maps the last value
+pub trait MapValue<T> {
+    type Cursor;
+    fn map_value<F, U>(self, f: F) -> (Self::Cursor, Option<U>)
+    where
+        F: FnOnce(T) -> U;
+}
+
+impl<'a, C, T> MapValue<T> for (C, Option<T>)
+where
+    C: Matchable<'a>,
+{
+    type Cursor = C;
+
+    fn map_value<F, U>(self, f: F) -> (C, Option<U>)
+    where
+        F: FnOnce(T) -> U,
+    {
+        (self.0, self.1.map(f))
+    }
+}
+
+/// A numeric token, see [`Matchable::number`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+/// A token with a "." or exponent is a Float, otherwise an Int.
+/// Integers too large for an i64 are parsed as a Float rather than failing
+impl FromStr for Number {
+    type Err = ParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if float_len(s) != Some(s.len()) {
+            return Err(error::failure("number", s));
+        }
+        if !s.contains(['.', 'e', 'E']) {
+            if let Ok(i) = s.parse::<i64>() {
+                return Ok(Number::Int(i));
+            }
+        }
+        Ok(Number::Float(s.parse::<f64>()?))
+    }
+}
+
+/// Whether a separator may follow the last item, see [`Matchable::list`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trailing {
+    /// "1,2" or "1,2," (eg config files)
+    Allow,
+    /// only "1,2" (eg JSON arrays)
+    Forbid,
+    /// only "1,2," (eg ";" terminated statements)
+    Require,
+}
+
+/// integer types which can be parsed in a given radix, eg hex
+pub trait FromStrRadix: Sized {
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, core::num::ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($t:ty)*) => {$(
+        impl FromStrRadix for $t {
+            #[inline]
+            fn from_str_radix(s: &str, radix: u32) -> Result<Self, core::num::ParseIntError> {
+                <$t>::from_str_radix(s, radix)
+            }
+        }
+    )*};
+}
+
+impl_from_str_radix!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
 fn start_end<R: RangeBounds<i32>>(rb: &R) -> (Option<i32>, Option<i32>) {
     let start = match rb.start_bound() {
         Bound::Included(&i) => Some(i),
@@ -88,13 +291,144 @@ fn start_end<R: RangeBounds<i32>>(rb: &R) -> (Option<i32>, Option<i32>) {
     (start, end)
 }
 
+fn digits_len(s: &str) -> usize {
+    s.bytes().take_while(|b| b.is_ascii_digit()).count()
+}
+
+// the value of exactly n ascii digits at the start of s, and the rest of s
+fn fixed_digits(s: &str, n: usize) -> Option<(u32, &str)> {
+    match s.get(..n) {
+        Some(d) if d.bytes().all(|b| b.is_ascii_digit()) => Some((d.parse().ok()?, &s[n..])),
+        _ => None,
+    }
+}
+
+// YYYY-MM-DD, with the day checked against the month (and leap years)
+fn iso_date(s: &str) -> Option<&str> {
+    let (y, s) = fixed_digits(s, 4)?;
+    let (m, s) = fixed_digits(s.strip_prefix('-')?, 2)?;
+    let (d, s) = fixed_digits(s.strip_prefix('-')?, 2)?;
+    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+    let days = match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&d).then_some(s)
+}
+
+// HH:MM
+fn iso_hh_mm(s: &str) -> Option<&str> {
+    let (h, s) = fixed_digits(s, 2)?;
+    let (m, s) = fixed_digits(s.strip_prefix(':')?, 2)?;
+    (h <= 23 && m <= 59).then_some(s)
+}
+
+// HH:MM:SS(.f+)? then an optional zone Z or [+-]HH:MM
+fn iso_time(s: &str) -> Option<&str> {
+    let s = iso_hh_mm(s)?;
+    let (sec, s) = fixed_digits(s.strip_prefix(':')?, 2)?;
+    if sec > 59 {
+        return None;
+    }
+    let s = match s.strip_prefix('.') {
+        Some(f) if digits_len(f) > 0 => &f[digits_len(f)..],
+        Some(_) => return None,
+        None => s,
+    };
+    match s.as_bytes().first() {
+        Some(b'Z') => Some(&s[1..]),
+        Some(b'+' | b'-') => iso_hh_mm(&s[1..]),
+        _ => Some(s),
+    }
+}
+
+// [+-]? (digits | digits '.' digits? | '.' digits) ([eE] [+-]? digits)?
+// returns the byte length of the float at the start of s, if any
+fn float_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let mut i = 0;
+    if matches!(b.first(), Some(b'+' | b'-')) {
+        i += 1;
+    }
+    let int_len = digits_len(&s[i..]);
+    i += int_len;
+    let mut frac_len = 0;
+    if b.get(i) == Some(&b'.') {
+        frac_len = digits_len(&s[i + 1..]);
+        if int_len == 0 && frac_len == 0 {
+            return None;
+        }
+        i += 1 + frac_len;
+    }
+    if int_len == 0 && frac_len == 0 {
+        return None;
+    }
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        let mut j = i + 1;
+        if matches!(b.get(j), Some(b'+' | b'-')) {
+            j += 1;
+        }
+        let exp_len = digits_len(&s[j..]);
+        // a dangling "e" is not part of the float
+        if exp_len > 0 {
+            i = j + exp_len;
+        }
+    }
+    Some(i)
+}
+
+fn strip_prefix_ignore_case<'s>(s: &'s str, word: &str) -> Option<&'s str> {
+    match s.get(..word.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(word) => Some(&s[word.len()..]),
+        _ => None,
+    }
+}
+
 enum NotFound {
     Eos,
     NoMatch,
 }
 
+// earliest (index, needle) of any of the needles, preferring the longer needle on a tie
+fn find_first<'n>(s: &str, needles: &[&'n str]) -> Option<(usize, &'n str)> {
+    needles
+        .iter()
+        .filter_map(|n| s.find(n).map(|i| (i, *n)))
+        .min_by(|(i1, n1), (i2, n2)| i1.cmp(i2).then(n2.len().cmp(&n1.len())))
+}
+
+// the input ran out needing more chars: Incomplete if partial, otherwise NoMatch
+fn too_few<'a, C: Matchable<'a>>(cur: &C, action: &'static str, needed: usize) -> ParsingError {
+    if cur.is_partial() {
+        ParsingError::Incomplete { needed }
+    } else {
+        ParsingError::NoMatch {
+            action,
+            args: "too few".into(),
+            offset: 0,
+        }
+    }
+}
+
+// a NoMatch saying how many chars a range-based matcher found, eg "expected 2, found 1"
+fn count_mismatch(action: &'static str, start: usize, end: usize, found: usize) -> ParsingError {
+    let expected = match end {
+        end if end == start => format!("{start}"),
+        usize::MAX => format!("at least {start}"),
+        end => format!("{start} to {end}"),
+    };
+    ParsingError::NoMatch {
+        action,
+        args: format!("expected {expected}, found {found}").into(),
+        offset: 0,
+    }
+}
+
 #[inline]
-fn find<'a, R, C, F, A1>(cur: C, rb: &R, pred: F, action: &'static str, args: &A1) -> C
+fn find<'a, R, C, F, A1>(cur: C, rb: &R, mut pred: F, action: &'static str, args: &A1) -> C
 where
     R: RangeBounds<i32>,
     C: Matchable<'a>,
@@ -109,48 +443,38 @@ where
     let (start, end) = start_end(rb);
     if let Some(end) = end {
         if end < 0 {
-            let e = ParsingError::NoMatch { action, args: "" };
+            let e = ParsingError::NoMatch {
+                action,
+                args: "".into(),
+                offset: 0,
+            };
             cur.log_failure(action, args, &e);
             return cur.set_error(e);
         }
     }
     //  set start to 0, if < 0
-    let start = start.unwrap_or_default() as usize;
-    let end = end.unwrap_or(i32::MAX) as usize;
-
-    if let Some((i, _t)) = s.match_indices(pred).next() {
-        if i >= start && i <= end + 1 {
-            let cur = cur.set_str(&s[i..]);
-            cur.log_success(action, args);
-            return cur;
-        }
-    } else if rb.contains(&0) {
-        // if 0 in range we can always return where we are
-        return cur.set_str(s);
+    let start = start.unwrap_or_default().max(0) as usize;
+    let end = end.map_or(usize::MAX, |end| end as usize);
+
+    // count (in chars) the leading chars not failing pred, stopping at the range end
+    let mut len = 0;
+    let mut i = s.len();
+    for (idx, c) in s.char_indices() {
+        if len == end || pred(c) {
+            i = idx;
+            break;
+        }
+        len += 1;
+    }
+    if len >= start {
+        let cur = cur.set_str(&s[i..]);
+        cur.log_success(action, args);
+        return cur;
+    }
+    let e = if i == s.len() && cur.is_partial() {
+        too_few(&cur, action, start - len)
     } else {
-        let len = s.chars().count();
-        if len < start {
-            let e = ParsingError::NoMatch {
-                action,
-                args: "len>start",
-            };
-            cur.log_failure(action, args, &e);
-            return cur.set_error(e);
-        } else if len > end {
-            let (i, _c) = s.char_indices().nth(end).unwrap();
-            let cur = cur.set_str(&s[i..]);
-            cur.log_success(action, args);
-            return cur;
-        } else if len == end || start_end(rb).1.is_none() {
-            let cur = cur.set_str("");
-            cur.log_success(action, args);
-            return cur;
-        }
-    }
-    // not found and len < end
-    let e = ParsingError::NoMatch {
-        action,
-        args: "no match",
+        count_mismatch(action, start, end, len)
     };
     cur.log_failure(action, args, &e);
     cur.set_error(e)
@@ -171,8 +495,31 @@ where
                 cur
             }
             None => {
-                let e = error::failure(msg, s);
-                cur.log_failure(msg, args, &e);
+                cur.log_failure(msg, args, &error::failure_static(msg));
+                cur.set_no_match(msg, args)
+            }
+        },
+        _ => cur,
+    }
+}
+
+/// as apply, for a choice of words. The error lists every word, eg one of ["{", "["]
+fn apply_alt<'a, C, F>(cur: C, f: F, msg: &'static str, words: &[&str]) -> C
+where
+    C: Matchable<'a>,
+    F: FnOnce(&str) -> Option<&str>,
+{
+    cur.log_inputs(msg, words);
+    match cur.str() {
+        Ok(s) => match f(s) {
+            Some(s) => {
+                let cur = cur.set_str(s);
+                cur.log_success(msg, words);
+                cur
+            }
+            None => {
+                let e = error::failure(msg, &format!("one of {words:?}"));
+                cur.log_failure(msg, words, &e);
                 cur.set_error(e)
             }
         },
@@ -180,14 +527,331 @@ where
     }
 }
 
+// the end of the block comment s starts with, or None if it is unterminated
+fn block_comment_end<'s>(s: &'s str, (open, close): (&str, &str), nested: bool) -> Option<&'s str> {
+    let mut depth = 0;
+    let mut s = s;
+    loop {
+        if let Some(rest) = s.strip_prefix(close) {
+            depth -= 1;
+            s = rest;
+            if depth == 0 {
+                return Some(s);
+            }
+        } else if let Some(rest) = s.strip_prefix(open).filter(|_| depth == 0 || nested) {
+            depth += 1;
+            s = rest;
+        } else {
+            let mut chars = s.chars();
+            chars.next()?;
+            s = chars.as_str();
+        }
+    }
+}
+
+fn skip_comments<'a, C>(cur: C, line_prefix: &str, block: Option<(&str, &str)>, nested: bool) -> C
+where
+    C: Matchable<'a>,
+{
+    let msg = "skip_comments";
+    cur.log_inputs(msg, (line_prefix, block));
+    let Ok(mut s) = cur.str() else {
+        return cur;
+    };
+    loop {
+        s = s.trim_start();
+        if !line_prefix.is_empty() && s.starts_with(line_prefix) {
+            s = s.find('\n').map_or("", |i| &s[i + 1..]);
+        } else if let Some(b) = block.filter(|b| s.starts_with(b.0)) {
+            match block_comment_end(s, b, nested) {
+                Some(rest) => s = rest,
+                None => {
+                    let c = cur.set_str(s);
+                    let e = error::failure(msg, "unterminated block comment");
+                    c.log_failure(msg, (line_prefix, block), &e);
+                    return c.set_error(ParsingError::Fatal(Some(Box::new(e))));
+                }
+            }
+        } else {
+            break;
+        }
+    }
+    let cur = cur.set_str(s);
+    cur.log_success(msg, (line_prefix, block));
+    cur
+}
+
+// matches word at the cursor, for text and expect. On a partial cursor, input ending
+// partway through the word is Incomplete rather than a NoMatch
+fn text_prefix<'a, C: Matchable<'a>>(cur: C, word: &str, action: &'static str) -> C {
+    if cur.is_partial() {
+        if let Ok(s) = cur.str() {
+            if s.len() < word.len() && word.starts_with(s) {
+                let e = too_few(&cur, action, word.len() - s.len());
+                return cur.set_error(e);
+            }
+        }
+    }
+    apply(cur, |s| s.strip_prefix(word), action, word)
+}
+
+// adds the cursor's debug_context span (if any) to the args of a NoMatch,
+// so that a failing parse_selection can be traced to its field
+fn labelled(label: &'static str, e: ParsingError) -> ParsingError {
+    match e {
+        ParsingError::NoMatch {
+            action,
+            args,
+            offset,
+        } if !label.is_empty() => ParsingError::NoMatch {
+            action,
+            args: match args.as_ref() {
+                "" => label.into(),
+                args => format!("{args} (in {label})").into(),
+            },
+            offset,
+        },
+        e => e,
+    }
+}
+
+// runs a parser that a combinator may backtrack over (an alternative, an optional
+// separator..) without the caller's commit, so that the parser's own NoMatch stays
+// recoverable. A commit made by the parser ends once it succeeds (see Matchable::commit)
+fn branch<'a, M, P>(cur: &M, parser: P) -> M
+where
+    M: Matchable<'a> + Clone,
+    P: FnOnce(M) -> M,
+{
+    let c = parser(cur.clone().set_committed(false));
+    match c.is_skip() {
+        true => c,
+        false => c.set_committed(cur.is_committed()),
+    }
+}
+
+// the loop of separated_list (and list): items separated by sep, stopping before a
+// separator not followed by an item, or once neither consumes anything
+fn separated<'a, M, P, S, C, T>(cur: M, item: &mut P, sep: &mut S) -> (M, Option<Vec<T>>)
+where
+    M: Matchable<'a> + Clone,
+    P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+    S: FnMut(M) -> M,
+    M::Cursor: Clone + TryInto<C> + From<C>,
+    C: TryInto<&'a str>,
+{
+    let mut vec = vec![];
+    let mut cur = match run_parser(&cur, item) {
+        Ok((s, t)) => {
+            vec.push(t);
+            cur.set_str(s)
+        }
+        Err(e) if e.is_recoverable() => return (cur, Some(vec)),
+        Err(e) => return (cur.set_error(e), None),
+    };
+    loop {
+        let after_sep = branch(&cur, &mut *sep);
+        if after_sep.is_fatal() {
+            return (after_sep, None);
+        }
+        if after_sep.is_skip() {
+            break;
+        }
+        match run_parser(&after_sep, item) {
+            // neither sep nor item consumed anything, so they would match forever
+            Ok((s, _t)) if cur.str().map_or(true, |before| before.len() == s.len()) => break,
+            Ok((s, t)) => {
+                vec.push(t);
+                cur = after_sep.set_str(s);
+            }
+            // roll back the separator
+            Err(e) if e.is_recoverable() => break,
+            Err(e) => return (cur.set_error(e), None),
+        }
+    }
+    (cur, Some(vec))
+}
+
+// runs a value-producing parser from the cursor's position, returning the remaining text
+fn run_parser<'a, M, P, C, T>(cur: &M, parser: &mut P) -> Result<(&'a str, T), ParsingError>
+where
+    M: Matchable<'a>,
+    P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+    M::Cursor: Clone + TryInto<C>,
+    C: TryInto<&'a str>,
+{
+    // the parser is a branch, so runs uncommitted (see Matchable::commit)
+    let (c, t) = parser.parse(
+        cur.cursor()
+            .clone()
+            .set_committed(false)
+            .try_into()
+            .unwrap_or_else(|_| panic!("Unexpected cursor() unwrap on valid cursor")),
+    )?;
+    match c.try_into() {
+        Ok(s) => Ok((s, t)),
+        Err(_e) => Err(ParsingError::NoMatch {
+            action: "",
+            args: "".into(),
+            offset: 0,
+        }),
+    }
+}
+
 pub trait Selectable<'a>: Matchable<'a> {
-    // fn parse(self) -> std::result::Result<Self::Cursor, BadMatch> {
+    // fn parse(self) -> core::result::Result<Self::Cursor, BadMatch> {
     //     CursorHelper::parse(self)
     // }
     fn get_selection(&self) -> Result<&'a str, ParsingError>;
     fn selection_end(self) -> Self;
     fn selection_start(self) -> Self;
 
+    /// runs the lexer, selecting all the text it consumed (like nom's recognize),
+    /// eg recognize(|c| c.float()).parse_selection_as_str() for the float as written
+    fn recognize<P>(self, mut lexer: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+    {
+        lexer(self.selection_start()).selection_end()
+    }
+
+    /// an optional "+" or "-" then the numeric lexer, selecting both (see recognize),
+    /// eg `signed(|c| c.digits(1..)).parse_selection::<i32>()`
+    fn signed<P>(self, mut lexer: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+    {
+        self.recognize(|c| lexer(c.chars_in(0..=1, &['+', '-'])))
+    }
+
+    /// byte offset of the cursor within the original input. Two limits: a cursor in error
+    /// reports the full input length (the NoMatch's own offset says where it failed), and a
+    /// sub-parser taking a &str (eg via parse_with) starts a fresh Cursor::from, so its
+    /// offsets, and those of its errors, are relative to where that sub-parser began
+    fn offset(&self) -> usize;
+
+    /// the [start, end) byte offsets of the selection within the original input,
+    /// eg for storing spans in an AST
+    fn selection_range(&self) -> Result<core::ops::Range<usize>, ParsingError>;
+
+    /// 1-based (line, column) of the cursor, columns counted in chars.
+    /// Useful for messages such as "expected ':' at line 3, col 12".
+    /// Measured from the same start as offset(), with the same limits
+    fn line_col(&self) -> (usize, usize);
+
+    /// the column of line_col, without counting the lines before the cursor's
+    fn column(&self) -> usize {
+        self.line_col().1
+    }
+
+    /// consumes nothing, failing unless the cursor is at the (1-based) column col of
+    /// its line. Columns are as line_col counts them, so a tab is a single column, and
+    /// in a sub-parser taking a &str they restart at 1 where the sub-parser began
+    fn at_column(self, col: usize) -> Self {
+        if self.is_skip() {
+            return self;
+        }
+        match self.column() {
+            c if c == col => {
+                self.log_success("at_column", col);
+                self
+            }
+            c => {
+                let e = error::failure("at_column", &format!("at column {c}"));
+                self.log_failure("at_column", col, &e);
+                self.set_error(e)
+            }
+        }
+    }
+
+    /// skips spaces and tabs, returning the (1-based) column reached, each tab
+    /// counting as a single column. eg to compare a block's indent with its parent's.
+    /// Columns are measured as for at_column
+    fn indent(self) -> (Self, Option<usize>) {
+        let c = apply(
+            self,
+            |s| Some(s.trim_start_matches([' ', '\t'])),
+            "indent",
+            "",
+        );
+        if c.is_skip() {
+            return (c, None);
+        }
+        let col = c.column();
+        (c, Some(col))
+    }
+
+    /// shrinks the selection to exclude leading and trailing ascii whitespace,
+    /// without moving the cursor. eg for a fixed-width field "13   "
+    fn trim_selection(self) -> Self;
+
+    /// starts a named span at the cursor, replacing any span of the same name
+    fn mark(self, name: &'static str) -> Self;
+
+    /// ends the named span at the cursor. NoMatch if the name was never marked
+    fn capture_to(self, name: &'static str) -> Self;
+
+    /// the text of a span captured with mark/capture_to
+    fn named(&self, name: &str) -> Result<&'a str, ParsingError>;
+
+    /// errors recorded by recover, in the order they occurred
+    fn errors(&self) -> &[ParsingError];
+
+    /// records an error without failing the cursor
+    fn push_error(self, e: ParsingError) -> Self;
+
+    /// runs parser, and if it fails with a recoverable error, records the error
+    /// and instead applies resync (eg scan_eol) from the original position.
+    /// Fatal errors are not recovered
+    fn recover<P, R>(self, mut parser: P, mut resync: R) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        R: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        self.log_inputs("recover", "");
+        if self.str().is_err() {
+            return self;
+        }
+        let c = branch(&self, &mut parser);
+        if c.str().is_ok() {
+            return c;
+        }
+        let Err(e) = c.clone().validate() else {
+            return c;
+        };
+        if !e.is_recoverable() {
+            return c;
+        }
+        self.log_failure("recover", "", &e);
+        resync(self).push_error(e)
+    }
+
+    /// runs the parser, annotating a NoMatch with the grammar production being parsed,
+    /// eg "while parsing `<time>`: ..". Unlike debug_context, this changes the error itself
+    fn annotate<P>(self, label: &'static str, mut parser: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        self.log_inputs("annotate", label);
+        if self.str().is_err() {
+            return self;
+        }
+        let c = parser(self.clone());
+        if c.str().is_ok() {
+            return c;
+        }
+        match c.clone().validate() {
+            Err(e) if e.is_recoverable() => {
+                let e = e.in_context(label);
+                self.log_failure("annotate", label, &e);
+                self.set_error(e)
+            }
+            _ => c,
+        }
+    }
+
     // fn de_nest_tuple<S, T, U>(((s, t), u): ((S, T), U)) -> (S, T, U) {
     //     (s, t, u)
     // }
@@ -228,24 +892,23 @@ pub trait Selectable<'a>: Matchable<'a> {
     //     Ok(res)
     // }
 
+    /// parses the selection with FromStr. Integer overflow is a fatal error,
+    /// other parse failures are a NoMatch
     fn parse_selection<T: FromStr + Debug>(self) -> (Self, Option<T>) {
-        self.log_inputs("parse_selection", std::any::type_name::<T>());
+        self.log_inputs("parse_selection", core::any::type_name::<T>());
         if let Ok(text) = self.get_selection() {
             if let Ok(_cur) = self.str() {
                 return match text.parse::<T>() {
                     Ok(t) => {
                         self.log_success_with_result(
-                            "----> get_selection",
-                            std::any::type_name::<T>(),
+                            "----> parse_selection",
+                            core::any::type_name::<T>(),
                             &t,
                         );
                         (self, Some(t))
                     }
-                    Err(..) => {
-                        let e = ParsingError::NoMatch {
-                            action: "FromStr",
-                            args: "",
-                        };
+                    Err(_) => {
+                        let e = labelled(self.label(), error::from_str_failure::<T>(text));
                         self.log_failure("parse_selection", "", &e);
                         (self.set_error(e), None)
                     }
@@ -255,23 +918,200 @@ pub trait Selectable<'a>: Matchable<'a> {
         (self, None)
     }
 
-    fn parse_opt_selection<T: FromStr + Debug>(self) -> (Self, Option<Option<T>>) {
-        self.log_inputs("parse_selection", std::any::type_name::<T>());
-        if let Ok(text) = self.get_selection() {
-            if let Ok(_cur) = self.str() {
-                return match text.parse::<T>() {
-                    Ok(t) => {
-                        self.log_success_with_result(
-                            "----> get_opt_selection",
-                            std::any::type_name::<T>(),
-                            &t,
+    /// fails with NoMatch unless the selection satisfies the predicate
+    fn verify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&'a str) -> bool,
+    {
+        self.log_inputs("verify", "");
+        if self.is_skip() {
+            return self;
+        }
+        match self.get_selection() {
+            Ok(text) if f(text) => {
+                self.log_success("verify", text);
+                self
+            }
+            _ => {
+                let e = error::failure_static("verify");
+                self.log_failure("verify", "", &e);
+                self.set_error(e)
+            }
+        }
+    }
+
+    /// parses the selection, failing with NoMatch unless the value satisfies the
+    /// predicate, eg .digits(2..=2).verify_parsed(|hh: &u32| *hh <= 23)
+    fn verify_parsed<T, F>(self, f: F) -> Self
+    where
+        T: FromStr + Debug,
+        F: FnOnce(&T) -> bool,
+        Self: Clone,
+    {
+        match self.clone().parse_selection::<T>() {
+            (_c, Some(t)) => self.verify(|_s| f(&t)),
+            (c, None) => c,
+        }
+    }
+
+    /// transforms the selection before parsing, eg stripping the underscores from "1_000"
+    /// ```
+    /// # use daisychain::prelude::*;
+    /// let (c, n) = Cursor::from("1_000_000;")
+    ///     .chars_in(1.., &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '_'])
+    ///     .map_selection(|s| s.replace('_', "").into())
+    ///     .parse_selection::<i32>();
+    /// assert_eq!(n, Some(1_000_000));
+    /// assert_eq!(c.str().unwrap(), ";");
+    /// ```
+    fn map_selection<F>(self, f: F) -> MappedSelection<'a, Self>
+    where
+        F: FnOnce(&'a str) -> Cow<'a, str>,
+    {
+        let mapped = match self.str() {
+            Ok(_) => self.get_selection().ok().map(f),
+            Err(_) => None,
+        };
+        MappedSelection { cur: self, mapped }
+    }
+
+    /// parses the selection as an integer in the given radix, eg 16 for "dEaDbeEf"
+    fn parse_selection_radix<T: FromStrRadix + Debug>(self, radix: u32) -> (Self, Option<T>) {
+        self.log_inputs("parse_selection_radix", radix);
+        if let Ok(text) = self.get_selection() {
+            if let Ok(_cur) = self.str() {
+                return match T::from_str_radix(text, radix) {
+                    Ok(t) => {
+                        self.log_success_with_result(
+                            "----> parse_selection_radix",
+                            core::any::type_name::<T>(),
+                            &t,
+                        );
+                        (self, Some(t))
+                    }
+                    Err(e) => {
+                        let e = labelled(self.label(), ParsingError::from(e));
+                        self.log_failure("parse_selection_radix", radix, &e);
+                        (self.set_error(e), None)
+                    }
+                };
+            }
+        }
+        (self, None)
+    }
+
+    /// decodes the selection as pairs of hex digits, eg after hex_digits(1..).
+    /// An odd number of digits is a NoMatch, rather than dropping the last nibble
+    fn parse_selection_hex_bytes(self) -> (Self, Option<Vec<u8>>) {
+        self.log_inputs("parse_selection_hex_bytes", "");
+        if let Ok(text) = self.get_selection() {
+            if let Ok(_cur) = self.str() {
+                let bytes = match text.len() % 2 {
+                    0 => (0..text.len())
+                        .step_by(2)
+                        .map(|i| {
+                            text.get(i..i + 2)
+                                .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                        })
+                        .collect::<Option<Vec<u8>>>()
+                        .ok_or_else(|| error::failure("parse_selection_hex_bytes", "invalid hex")),
+                    _ => Err(error::failure("parse_selection_hex_bytes", "odd length")),
+                };
+                return match bytes {
+                    Ok(v) => {
+                        self.log_success_with_result("----> parse_selection_hex_bytes", "", &v);
+                        (self, Some(v))
+                    }
+                    Err(e) => {
+                        let e = labelled(self.label(), e);
+                        self.log_failure("parse_selection_hex_bytes", "", &e);
+                        (self.set_error(e), None)
+                    }
+                };
+            }
+        }
+        (self, None)
+    }
+
+    /// converts the selection with f rather than FromStr, eg a date in a given format.
+    /// An error from f is set on the cursor as parse_selection's would be
+    fn parse_selection_with<T, F>(self, f: F) -> (Self, Option<T>)
+    where
+        T: Debug,
+        F: FnOnce(&'a str) -> Result<T, ParsingError>,
+    {
+        self.log_inputs("parse_selection_with", core::any::type_name::<T>());
+        if let Ok(text) = self.get_selection() {
+            if let Ok(_cur) = self.str() {
+                return match f(text) {
+                    Ok(t) => {
+                        self.log_success_with_result(
+                            "----> parse_selection_with",
+                            core::any::type_name::<T>(),
+                            &t,
+                        );
+                        (self, Some(t))
+                    }
+                    Err(e) => {
+                        let e = labelled(self.label(), e);
+                        self.log_failure("parse_selection_with", "", &e);
+                        (self.set_error(e), None)
+                    }
+                };
+            }
+        }
+        (self, None)
+    }
+
+    /// as parse_selection, but yielding default() when the selection is empty or fails
+    /// to parse, eg a malformed optional field. A fatal failure (eg overflow) is still
+    /// an error. An erroring cursor is left as is
+    fn parse_selection_or<T, F>(self, default: F) -> (Self, Option<T>)
+    where
+        T: FromStr + Debug,
+        F: FnOnce() -> T,
+    {
+        self.log_inputs("parse_selection_or", core::any::type_name::<T>());
+        if self.is_skip() {
+            return (self, None);
+        }
+        let t = match self.get_selection() {
+            Ok(text) if !text.is_empty() => match text.parse::<T>() {
+                Ok(t) => t,
+                // only a recoverable failure falls back to the default, not eg overflow
+                Err(_) => match error::from_str_failure::<T>(text) {
+                    e if !e.is_recoverable() => {
+                        let e = labelled(self.label(), e);
+                        self.log_failure("parse_selection_or", "", &e);
+                        return (self.set_error(e), None);
+                    }
+                    _ => default(),
+                },
+            },
+            _ => default(),
+        };
+        self.log_success_with_result("----> parse_selection_or", core::any::type_name::<T>(), &t);
+        (self, Some(t))
+    }
+
+    fn parse_opt_selection<T: FromStr + Debug>(self) -> (Self, Option<Option<T>>) {
+        self.log_inputs("parse_opt_selection", core::any::type_name::<T>());
+        if let Ok(text) = self.get_selection() {
+            if let Ok(_cur) = self.str() {
+                return match text.parse::<T>() {
+                    Ok(t) => {
+                        self.log_success_with_result(
+                            "----> parse_opt_selection",
+                            core::any::type_name::<T>(),
+                            &t,
                         );
                         (self, Some(Some(t)))
                     }
                     Err(..) => {
                         self.log_success_with_result(
-                            "----> get_opt_selection",
-                            std::any::type_name::<T>(),
+                            "----> parse_opt_selection",
+                            core::any::type_name::<T>(),
                             "None",
                         );
                         (self, Some(None))
@@ -316,21 +1156,62 @@ pub trait Selectable<'a>: Matchable<'a> {
     //     todo!()
     // }
 
+    /// parses the selection and appends it to vec. A selection which fails to parse
+    /// is a NoMatch naming the offending text; an erroring cursor is left as is
     fn append_last<X, T>(self, vec: &mut X) -> Self
     where
         // from iter not used but distinguishes the case of Extend by ref
         X: Extend<T> + FromIterator<T>,
         T: FromStr,
     {
-        if let Ok(text) = self.get_selection() {
-            let res_t = T::from_str(text);
-            if let Ok(t) = res_t {
-                vec.extend(std::iter::once(t));
-            } else {
-                return self.set_error(ParsingError::NoMatch {
-                    action: "",
-                    args: "",
-                });
+        let msg = "append_last";
+        self.log_inputs(msg, core::any::type_name::<T>());
+        if self.is_skip() {
+            return self;
+        }
+        let e = match self.get_selection() {
+            Ok(text) => match T::from_str(text) {
+                Ok(t) => {
+                    vec.extend(core::iter::once(t));
+                    self.log_success(msg, text);
+                    return self;
+                }
+                Err(_) => error::failure(msg, text),
+            },
+            Err(e) => e,
+        };
+        self.log_failure(msg, core::any::type_name::<T>(), &e);
+        self.set_error(e)
+    }
+
+    /// like repeat, but folds the selection left by each match of item into an
+    /// accumulator, eg summing digit groups without collecting them
+    fn fold_many<R, P, B, F>(self, range: R, mut item: P, init: B, mut f: F) -> (Self, Option<B>)
+    where
+        R: RangeBounds<i32> + Debug,
+        P: FnMut(Self) -> Self,
+        F: FnMut(B, &'a str) -> B,
+        Self: Clone,
+    {
+        let mut acc = Some(init);
+        let c = self.repeat(range, |c| {
+            let c = item(c);
+            if let (Ok(_), Ok(text)) = (c.str(), c.get_selection()) {
+                acc = acc.take().map(|b| f(b, text));
+            }
+            c
+        });
+        if c.is_skip() {
+            return (c, None);
+        }
+        (c, acc)
+    }
+
+    /// appends the selected text (unparsed) to out. Skipped if the cursor is in error
+    fn push_selection(self, out: &mut Vec<&'a str>) -> Self {
+        if self.str().is_ok() {
+            if let Ok(text) = self.get_selection() {
+                out.push(text);
             }
         }
         self
@@ -343,7 +1224,7 @@ pub trait Selectable<'a>: Matchable<'a> {
         let msg = "select_with";
         let args = "";
         self.log_inputs(msg, args);
-        if let Ok(s) = self.str() {
+        if self.str().is_ok() {
             let t = parser(self.selection_start());
             match t.str() {
                 Ok(tt) => {
@@ -352,7 +1233,7 @@ pub trait Selectable<'a>: Matchable<'a> {
                     return t.selection_end();
                 }
                 _ => {
-                    let e = error::failure(msg, s);
+                    let e = error::failure_static(msg);
                     t.log_failure(msg, args, &e);
                     return t.set_error(e);
                 }
@@ -361,6 +1242,116 @@ pub trait Selectable<'a>: Matchable<'a> {
         self
     }
 
+    /// matches a quoted string such as "a\"b", honouring the escape char, and
+    /// selects the (still escaped) text between the quotes. See unescape()
+    fn quoted(self, quote: char, escape: char) -> Self {
+        apply(
+            self.char(quote).selection_start(),
+            |s| {
+                let mut chars = s.char_indices();
+                while let Some((i, c)) = chars.next() {
+                    if c == escape {
+                        chars.next()?;
+                    } else if c == quote {
+                        return Some(&s[i..]);
+                    }
+                }
+                None
+            },
+            "quoted",
+            "",
+        )
+        .selection_end()
+        .char(quote)
+    }
+
+    /// decodes escapes while scanning (like nom's escaped_transform): consumes normal
+    /// chars, and for each control char the next char mapped by transform,
+    /// eg 'n' to '\n'. An escape which transform maps to None is a NoMatch
+    fn escaped<N, T>(self, mut normal: N, control: char, mut transform: T) -> (Self, Option<String>)
+    where
+        N: FnMut(char) -> bool,
+        T: FnMut(char) -> Option<char>,
+    {
+        let msg = "escaped";
+        self.log_inputs(msg, control);
+        let Ok(s) = self.str() else {
+            return (self, None);
+        };
+        let mut decoded = String::new();
+        let mut chars = s.char_indices();
+        let mut end = s.len();
+        while let Some((i, c)) = chars.next() {
+            if c == control {
+                let e = match chars.next().map(|(_, c)| (c, transform(c))) {
+                    Some((_, Some(t))) => {
+                        decoded.push(t);
+                        continue;
+                    }
+                    Some((c, None)) => error::failure(msg, &format!("unknown escape {control}{c}")),
+                    None => too_few(&self, msg, 1),
+                };
+                let cur = self.set_str(&s[i..]);
+                cur.log_failure(msg, control, &e);
+                return (cur.set_error(e), None);
+            } else if normal(c) {
+                decoded.push(c);
+            } else {
+                end = i;
+                break;
+            }
+        }
+        let cur = self.set_str(&s[end..]);
+        cur.log_success_with_result(msg, control, &decoded);
+        (cur, Some(decoded))
+    }
+
+    /// from an opening delimiter to its matching close, counting nesting depth
+    /// (but not aware of quotes). The selection is the text between the delimiters
+    /// eg balanced('(', ')') on "(a(b)c) d" selects "a(b)c", leaving " d"
+    fn balanced(self, open: char, close: char) -> Self {
+        apply(
+            self.char(open).selection_start(),
+            |s| {
+                let mut depth = 0;
+                for (i, c) in s.char_indices() {
+                    if c == close {
+                        if depth == 0 {
+                            return Some(&s[i..]);
+                        }
+                        depth -= 1;
+                    } else if c == open {
+                        depth += 1;
+                    }
+                }
+                None
+            },
+            "balanced",
+            "",
+        )
+        .selection_end()
+        .char(close)
+    }
+
+    /// the current selection with escape chars removed (the char following
+    /// an escape is taken literally)
+    fn unescape(&self, escape: char) -> Result<String, ParsingError> {
+        let text = self.get_selection()?;
+        let mut unescaped = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == escape {
+                match chars.next() {
+                    Some(c) => unescaped.push(c),
+                    None => return Err(error::failure("unescape", text)),
+                }
+            } else {
+                unescaped.push(c);
+            }
+        }
+        Ok(unescaped)
+    }
+
     // fn take_last<M, T>(self, mut target: M) -> Self
     // where
     //     M: AsMut<T>,
@@ -395,36 +1386,161 @@ pub trait Matchable<'a>: Sized {
 
     fn cursor(&self) -> &Self::Cursor;
 
-    fn str(&self) -> std::result::Result<&'a str, ParsingError>;
+    fn str(&self) -> core::result::Result<&'a str, ParsingError>;
     fn set_str(self, s: &'a str) -> Self;
     fn set_error(self, e: ParsingError) -> Self;
 
+    /// fails with a NoMatch for the action, its args followed by the text found instead.
+    /// A Cursor only formats the error if it is asked for, so backtracking over the
+    /// failure does not allocate
+    fn set_no_match(self, action: &'static str, args: &str) -> Self {
+        let found = self.str().unwrap_or_default();
+        let e = error::failure(action, &error::with_found(args, found));
+        self.set_error(e)
+    }
+
+    /// whether the cursor is in error, and the error is not recoverable (see Recoverable)
+    fn is_fatal(&self) -> bool {
+        false
+    }
+
+    /// names the span being parsed, for logging and for the args of parse_selection errors.
+    /// The error name is carried by the cursor (see label); the logging name remains in
+    /// effect (on this thread) until the next debug_context
     #[inline]
     fn debug_context(self, span_name: &'static str) -> Self {
+        #[cfg(feature = "debug-trace")]
+        crate::trace::group(span_name);
         if log_enabled!(target: LOG_TARGET, Trace) {
-            LABEL.with(|f| f.set("")); // blank the span name before logging
+            set_log_label(""); // blank the span name before logging
             self.log_success("debug_context", span_name);
-            LABEL.with(|f| f.set(span_name));
         }
+        set_log_label(span_name);
+        self.set_label(span_name)
+    }
+
+    /// runs the parser under the span name label (as debug_context), then restores
+    /// the enclosing span name, so nested parsers don't clobber their caller's label
+    fn scoped<P>(self, label: &'static str, mut parser: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+    {
+        let outer = log_label();
+        let outer_label = self.label();
+        let c = parser(self.debug_context(label));
+        #[cfg(feature = "debug-trace")]
+        if !outer.is_empty() {
+            crate::trace::group(outer);
+        }
+        set_log_label(outer);
+        c.set_label(outer_label)
+    }
+
+    // fn validate(self) -> core::result::Result<Self, ParseError>;
+    fn validate(self) -> core::result::Result<Self::DeTuple, ParsingError>;
+
+    /// the "cut" of PEG parsing: once a branch is known to be the right one (eg after
+    /// its keyword), the next NoMatch is made Fatal, so alternatives are not tried and the
+    /// error is reported where it occurred. The flag is cleared by that error, and is scoped
+    /// to the branch: a parser tried by alt, opt, repeat and the like runs uncommitted,
+    /// and once it succeeds the cursor is committed only if the caller's was
+    fn commit(self) -> Self {
+        self
+    }
+
+    /// whether the cursor is committed (see commit)
+    fn is_committed(&self) -> bool {
+        false
+    }
+
+    /// sets or clears the commit flag without logging, as combinators do to scope a
+    /// commit to the parser they try (see commit)
+    fn set_committed(self, _committed: bool) -> Self {
         self
     }
 
-    // fn validate(self) -> std::result::Result<Self, ParseError>;
-    fn validate(self) -> std::result::Result<Self::DeTuple, ParsingError>;
+    /// whether running out of input is Incomplete rather than NoMatch (see Cursor::partial)
+    fn is_partial(&self) -> bool {
+        false
+    }
+
+    /// the span name given by debug_context (or scoped), which parse_selection errors
+    /// mention. Carried by the cursor and its clones, but not into a sub-parser that
+    /// starts a fresh Cursor from a &str
+    fn label(&self) -> &'static str {
+        ""
+    }
+
+    /// sets the span name returned by label, see debug_context
+    fn set_label(self, _label: &'static str) -> Self {
+        self
+    }
 
     fn is_skip(&self) -> bool {
         self.str().is_err()
     }
 
+    /// the next char of the input, without consuming it. None at the end of the input
+    /// or on an erroring cursor. For dispatching eg match c.peek_char() { Some('[') => .. }
+    fn peek_char(&self) -> Option<char> {
+        self.str().ok()?.chars().next()
+    }
+
+    /// matches the regex at the cursor (not later in the input), the match becoming
+    /// the selection. A pattern starting with ^ avoids searching beyond the cursor
+    #[cfg(feature = "regex")]
+    fn regex(self, re: &regex::Regex) -> Self {
+        apply(
+            self,
+            |s| re.find(s).filter(|m| m.start() == 0).map(|m| &s[m.end()..]),
+            "regex",
+            re.as_str(),
+        )
+    }
+
+    /// strips a leading UTF-8 byte order mark ("\u{FEFF}") if there is one,
+    /// so is safe to place at the start of any parse of a file
+    fn skip_bom(self) -> Self {
+        apply(
+            self,
+            |s| Some(s.strip_prefix('\u{FEFF}').unwrap_or(s)),
+            "skip_bom",
+            "",
+        )
+    }
+
     fn noop(self) -> Self {
         apply(self, |s| Some(s), "noop", "")
     }
 
+    /// always succeeds, consuming nothing. The identity for building combinators,
+    /// eg if cond { c.succeed() } else { c.fail("cond") }
+    fn succeed(self) -> Self {
+        apply(self, |s| Some(s), "succeed", "")
+    }
+
+    /// always fails with a NoMatch for action, consuming nothing
+    fn fail(self, action: &'static str) -> Self {
+        apply(self, |_s| None, action, "")
+    }
+
     #[inline]
     fn ws(self) -> Self {
         apply(self, |s| Some(s.trim_start()), "ws", "")
     }
 
+    /// as ws(), but also skipping line comments (line_prefix to the end of the line)
+    /// and block comments (block.0 to block.1), eg skip_comments("//", Some(("/*", "*/"))).
+    /// Block comments don't nest (see skip_nested_comments). An unterminated block comment is Fatal
+    fn skip_comments(self, line_prefix: &str, block: Option<(&str, &str)>) -> Self {
+        skip_comments(self, line_prefix, block, false)
+    }
+
+    /// as skip_comments, but block comments nest, eg "/* a /* b */ c */" is a single comment
+    fn skip_nested_comments(self, line_prefix: &str, block: Option<(&str, &str)>) -> Self {
+        skip_comments(self, line_prefix, block, true)
+    }
+
     fn non_ws(self) -> Self {
         apply(
             self,
@@ -443,9 +1559,65 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
+    /// as ws, but requiring at least one whitespace char, so that tokens are separated
+    fn ws1(self) -> Self {
+        apply(
+            self,
+            |s| Some(s.trim_start()).filter(|t| t.len() < s.len()),
+            "ws1",
+            "",
+        )
+    }
+
+    /// as hws, but requiring at least one horizontal whitespace char
+    fn hws1(self) -> Self {
+        apply(
+            self,
+            |s| {
+                Some(s.trim_start_matches(|c: char| c.is_whitespace() && c != '\n' && c != '\r'))
+                    .filter(|t| t.len() < s.len())
+            },
+            "hws1",
+            "",
+        )
+    }
+
+    /// exactly one "\n" or "\r\n". Unlike ws, fails if there is no line break
+    fn newline(self) -> Self {
+        apply(
+            self,
+            |s| s.strip_prefix('\n').or_else(|| s.strip_prefix("\r\n")),
+            "newline",
+            "",
+        )
+    }
+
+    /// skips any number of lines that are empty or only horizontal whitespace,
+    /// leaving the cursor at the start of the next non-blank line
+    fn blank_lines(self) -> Self {
+        apply(
+            self,
+            |mut s| loop {
+                let t = s.trim_start_matches(|c: char| c.is_whitespace() && c != '\n' && c != '\r');
+                match t.strip_prefix('\n').or_else(|| t.strip_prefix("\r\n")) {
+                    Some(next) => s = next,
+                    None if t.is_empty() => return Some(t),
+                    None => return Some(s),
+                }
+            },
+            "blank_lines",
+            "",
+        )
+    }
+
     // "" means always match. use eos() to test for end of string/strea,
     fn text(self, word: &str) -> Self {
-        apply(self, |s| s.strip_prefix(word), "text", word)
+        text_prefix(self, word, "text")
+    }
+
+    /// like text, but the error's action is "expect", eg for a keyword a grammar requires
+    fn expect(self, word: &'static str) -> Self {
+        text_prefix(self, word, "expect")
     }
 
     fn char(self, ch: char) -> Self {
@@ -454,24 +1626,76 @@ pub trait Matchable<'a>: Sized {
         apply(self, |s| s.strip_prefix(ch), "char", str)
     }
 
+    /// a single char from the set
+    fn one_of(self, chars: &[char]) -> Self {
+        apply(
+            self,
+            |s| s.strip_prefix(|c: char| chars.contains(&c)),
+            "one_of",
+            "",
+        )
+    }
+
+    /// a single char not in the set. NoMatch at the end of the input
+    fn none_of(self, chars: &[char]) -> Self {
+        apply(
+            self,
+            |s| s.strip_prefix(|c: char| !chars.contains(&c)),
+            "none_of",
+            "",
+        )
+    }
+
     /// text_many(0..1, "word")
     fn maybe(self, word: &str) -> Self {
         apply(self, |s| s.strip_prefix(word).or(Some(s)), "maybe", word)
     }
 
+    /// the first of the words that matches, so the order matters: text_alt(&["<", "<="])
+    /// never matches "<=" whole. See text_alt_longest, which tokenizers usually want
     fn text_alt(self, words: &[&str]) -> Self {
-        apply(
+        apply_alt(
+            self,
+            |s| words.iter().find_map(|w| s.strip_prefix(w)),
+            "text_alt",
+            words,
+        )
+    }
+
+    /// the longest of the words that matches (maximal munch), whatever their order,
+    /// so text_alt_longest(&["<", "<="]) matches "<=" in "<= 3"
+    fn text_alt_longest(self, words: &[&str]) -> Self {
+        apply_alt(
             self,
             |s| {
-                for w in words {
-                    if s.starts_with(w) {
-                        return s.strip_prefix(w);
-                    }
-                }
-                None
+                words
+                    .iter()
+                    .filter_map(|w| s.strip_prefix(w))
+                    .min_by_key(|rest| rest.len())
             },
-            "text_alt",
-            words.first().unwrap_or(&"no words"),
+            "text_alt_longest",
+            words,
+        )
+    }
+
+    /// as text() but comparing with ASCII case folding only (Unicode case folding is not applied).
+    /// The matched input keeps its original casing
+    fn text_ignore_case(self, word: &str) -> Self {
+        apply(
+            self,
+            |s| strip_prefix_ignore_case(s, word),
+            "text_ignore_case",
+            word,
+        )
+    }
+
+    /// as text_alt() but comparing with ASCII case folding only
+    fn text_alt_ignore_case(self, words: &[&str]) -> Self {
+        apply_alt(
+            self,
+            |s| words.iter().find_map(|w| strip_prefix_ignore_case(s, w)),
+            "text_alt_ignore_case",
+            words,
         )
     }
 
@@ -485,6 +1709,29 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
+    /// like end_of_stream, but on failure the error's args show the unconsumed text
+    fn expect_eof(self) -> Self {
+        self.log_inputs("expect_eof", "");
+        match self.str() {
+            Ok(s) if !s.is_empty() => {
+                let e = error::failure("expect_eof", &crate::util::unconsumed(s));
+                self.log_failure("expect_eof", "", &e);
+                self.set_error(e)
+            }
+            Ok(_) => {
+                self.log_success("expect_eof", "");
+                self
+            }
+            Err(_) => self,
+        }
+    }
+
+    /// alias for end_of_stream
+    #[inline]
+    fn eof(self) -> Self {
+        self.end_of_stream()
+    }
+
     #[allow(clippy::wrong_self_convention)]
     fn end_of_line(self) -> Self {
         #[allow(clippy::unnecessary_lazy_evaluations)]
@@ -507,19 +1754,80 @@ pub trait Matchable<'a>: Sized {
         apply(self, |s| s.find(needle).map(|i| &s[i..]), "find", needle)
     }
 
-    // from Xpath's substring-after.  scan("blob") === find("blob").text("blob")
-    // synonyms: from, read, skim, skip_over, consume, scan
-    fn scan_text(self, needle: &str) -> Self {
-        apply(
-            self,
-            |s| s.find(needle).map(|i| &s[i + needle.len()..]),
+    /// up to (but not including) where the lexer would next match, or to the end of the
+    /// input if it never does, the skipped text becoming the selection. The lexer is tried
+    /// at each char in turn, so is best kept cheap to fail. A fatal error is propagated
+    fn take_until_parser<P>(self, mut lexer: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        let msg = "take_until_parser";
+        self.log_inputs(msg, "");
+        let Ok(s) = self.str() else {
+            return self;
+        };
+        let positions = s.char_indices().map(|(i, _c)| i);
+        for i in positions.chain(core::iter::once(s.len())) {
+            let c = branch(&self.clone().set_str(&s[i..]), &mut lexer);
+            if c.is_fatal() {
+                let e = c.validate().err().unwrap_or_default();
+                self.log_failure(msg, "", &e);
+                return self.set_error(e);
+            }
+            if !c.is_skip() {
+                let cur = self.set_str(&s[i..]);
+                cur.log_success(msg, "");
+                return cur;
+            }
+        }
+        let cur = self.set_str(&s[s.len()..]);
+        cur.log_success(msg, "");
+        cur
+    }
+
+    // from Xpath's substring-after.  scan("blob") === find("blob").text("blob")
+    // synonyms: from, read, skim, skip_over, consume, scan
+    fn scan_text(self, needle: &str) -> Self {
+        apply(
+            self,
+            |s| s.find(needle).map(|i| &s[i + needle.len()..]),
             "scan",
             "needle",
         )
     }
 
+    /// like find, but stops before the earliest of several needles. NoMatch if none are found
+    fn find_any(self, needles: &[&str]) -> Self {
+        apply(
+            self,
+            |s| find_first(s, needles).map(|(i, _n)| &s[i..]),
+            "find_any",
+            needles.first().unwrap_or(&"no needles"),
+        )
+    }
+
+    /// like scan_text, but skips over the earliest of several needles
+    /// (the longest, if several start at the same place). NoMatch if none are found
+    fn scan_to_any(self, needles: &[&str]) -> Self {
+        apply(
+            self,
+            |s| find_first(s, needles).map(|(i, n)| &s[i + n.len()..]),
+            "scan_to_any",
+            needles.first().unwrap_or(&"no needles"),
+        )
+    }
+
+    /// all the remaining input (possibly none), eg a message body after its headers.
+    /// A following parse_selection_as_str yields the whole tail
+    fn rest(self) -> Self {
+        apply(self, |s| Some(&s[s.len()..]), "rest", "")
+    }
+
     // read-to-and-over the end of line (or eos)
     // read_eol, skim_eol, skip_over_eof, scan_eol,
+    /// to after the next "\n", or to the end of the input if there is none.
+    /// See next_line where the line terminator is required
     fn scan_eol(self) -> Self {
         const LEN: usize = ("\n").len();
         apply(
@@ -530,6 +1838,71 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
+    /// to after the next "\n", which unlike scan_eol must be present (NoMatch otherwise).
+    /// Suits formats where every line is terminated, so a truncated last line is an error
+    fn next_line(self) -> Self {
+        if self.is_partial() && matches!(self.str(), Ok(s) if !s.contains('\n')) {
+            let e = too_few(&self, "next_line", 1);
+            return self.set_error(e);
+        }
+        apply(self, |s| s.find('\n').map(|i| &s[i + 1..]), "next_line", "")
+    }
+
+    /// up to (but not including) the next "\n" or "\r\n", or to the end of the input
+    fn rest_of_line(self) -> Self {
+        apply(
+            self,
+            |s| {
+                let i = s.find('\n').unwrap_or(s.len());
+                let i = if s[..i].ends_with('\r') { i - 1 } else { i };
+                Some(&s[i..])
+            },
+            "rest_of_line",
+            "",
+        )
+    }
+
+    /// the rest of the line and its terminator, if any. NoMatch at the end of the input
+    fn line(self) -> Self {
+        apply(
+            self,
+            |s| match s.find('\n') {
+                _ if s.is_empty() => None,
+                Some(i) => Some(&s[i + 1..]),
+                None => Some(""),
+            },
+            "line",
+            "",
+        )
+    }
+
+    /// advances over the (possibly empty) leading run of chars satisfying pred
+    fn take_while<F>(self, mut pred: F) -> Self
+    where
+        F: FnMut(char) -> bool,
+    {
+        apply(
+            self,
+            |s| Some(s.trim_start_matches(|c| pred(c))),
+            "take_while",
+            "",
+        )
+    }
+
+    /// advances up to (but not over) the first char satisfying pred,
+    /// or to end-of-stream if there is none
+    fn take_until<F>(self, mut pred: F) -> Self
+    where
+        F: FnMut(char) -> bool,
+    {
+        apply(
+            self,
+            |s| Some(s.find(|c| pred(c)).map_or("", |i| &s[i..])),
+            "take_until",
+            "",
+        )
+    }
+
     fn chars_in<R: RangeBounds<i32>>(self, range: R, chars: &[char]) -> Self {
         find(
             self,
@@ -552,17 +1925,56 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
-    fn chars_any<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
+    /// consumes one or more chars up to a delimiter (or end-of-stream), selecting the token.
+    /// Fails with NoMatch if the cursor is already on a delimiter
+    fn token(self, delimiters: &[char]) -> Self {
         find(
             self,
-            &range,
-            |_c| false,
-            // |s| Some(s.trim_start_matches(|c: char| !chars.contains(&c))),
-            "chars_any",
-            &range,
+            &(1..),
+            |c| delimiters.contains(&c),
+            "token",
+            &delimiters,
         )
     }
 
+    /// consumes between start and end chars (of any kind), failing if fewer than start are available
+    fn chars_any<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
+        let action = "chars_any";
+        self.log_inputs(action, &range);
+        let Ok(s) = self.str() else {
+            return self;
+        };
+        let (start, end) = start_end(&range);
+        let empty = matches!(end, Some(end) if end < 0);
+        let start = start.unwrap_or_default().max(0) as usize;
+        let end = end.map_or(usize::MAX, |end| end.max(0) as usize);
+        // consume up to end chars (counting chars, not bytes)
+        let (count, i) = s
+            .char_indices()
+            .map(|(i, _c)| i)
+            .chain(core::iter::once(s.len()))
+            .enumerate()
+            .take_while(|&(n, _i)| n <= end)
+            .last()
+            .unwrap_or((0, 0));
+        if empty || start > end {
+            let e = error::failure_static(action);
+            self.log_failure(action, &range, &e);
+            return self.set_error(e);
+        }
+        if count < start {
+            let e = match self.is_partial() {
+                true => too_few(&self, action, start - count),
+                false => count_mismatch(action, start, end, count),
+            };
+            self.log_failure(action, &range, &e);
+            return self.set_error(e);
+        }
+        let cur = self.set_str(&s[i..]);
+        cur.log_success(action, &range);
+        cur
+    }
+
     fn chars_match<R: RangeBounds<i32> + Debug, F>(self, range: R, mut pred: F) -> Self
     where
         F: FnMut(char) -> bool,
@@ -577,6 +1989,18 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
+    /// between min and max (inclusive) chars matching pred, shorthand for
+    /// chars_match(min..=max, pred), eg between(2, 2, |c| c.is_ascii_hexdigit()) for a
+    /// fixed-width field
+    fn between<F>(self, min: i32, max: i32, pred: F) -> Self
+    where
+        F: FnMut(char) -> bool,
+    {
+        self.chars_match(min..=max, pred)
+    }
+
+    /// ascii digits, the range being the number of digits. At most the range end are
+    /// consumed, so digits(2..=2) on "1234" leaves "34" for the next fixed-width field
     fn digits<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
         find(
             self,
@@ -588,6 +2012,187 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
+    /// like chars_any, but counting grapheme clusters (user-perceived characters),
+    /// so that "e" followed by a combining accent counts as one. Requires feature "unicode"
+    #[cfg(feature = "unicode")]
+    fn graphemes<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let (start, end) = start_end(&range);
+        apply(
+            self,
+            |s| {
+                if matches!(end, Some(end) if end < 0) {
+                    return None;
+                }
+                let start = start.unwrap_or_default().max(0) as usize;
+                let end = end.map_or(usize::MAX, |end| end as usize);
+                let (count, i) = s
+                    .grapheme_indices(true)
+                    .map(|(i, _g)| i)
+                    .chain(core::iter::once(s.len()))
+                    .enumerate()
+                    .take_while(|&(n, _i)| n <= end)
+                    .last()
+                    .unwrap_or((0, 0));
+                (count >= start && start <= end).then(|| &s[i..])
+            },
+            "graphemes",
+            "",
+        )
+    }
+
+    /// exactly n bytes, for fixed-width fields. Fails rather than split a multi-byte char
+    fn bytes(self, n: usize) -> Self {
+        if let Ok(s) = self.str() {
+            if s.len() < n && self.is_partial() {
+                let e = too_few(&self, "bytes", n - s.len());
+                return self.set_error(e);
+            }
+        }
+        apply(self, |s| s.get(n..), "bytes", "")
+    }
+
+    /// exactly n chars, whatever they are. NoMatch if fewer than n remain
+    fn skip_chars(self, n: usize) -> Self {
+        if let Ok(s) = self.str() {
+            let len = s.chars().take(n).count();
+            if len < n {
+                let e = too_few(&self, "skip_chars", n - len);
+                return self.set_error(e);
+            }
+        }
+        apply(
+            self,
+            |s| Some(s.char_indices().nth(n).map_or("", |(i, _)| &s[i..])),
+            "skip_chars",
+            "",
+        )
+    }
+
+    /// the (possibly empty) run of a repeated char, returning its length,
+    /// eg to compare indentation levels
+    fn skip_run(self, ch: char) -> (Self, Option<usize>) {
+        self.log_inputs("skip_run", ch);
+        let Ok(s) = self.str() else {
+            return (self, None);
+        };
+        let rest = s.trim_start_matches(ch);
+        let n = (s.len() - rest.len()) / ch.len_utf8();
+        let c = self.set_str(rest);
+        c.log_success_with_result("skip_run", ch, n);
+        (c, Some(n))
+    }
+
+    /// hexadecimal digits [0-9a-fA-F]. Use maybe("0x") for an optional prefix
+    fn hex_digits<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
+        find(
+            self,
+            &range,
+            |c| !c.is_ascii_hexdigit(),
+            "hex_digits",
+            &range,
+        )
+    }
+
+    /// optional sign followed by digits, the range applying to the number of digits.
+    /// The sign is included in the selection, so `parse_selection::<i32>()` works directly
+    fn int<R: RangeBounds<i32> + Debug>(self, range: R) -> Self {
+        let (start, end) = start_end(&range);
+        apply(
+            self,
+            |s| {
+                let sign = usize::from(s.starts_with(['+', '-']));
+                let n = digits_len(&s[sign..]);
+                let n = end.map_or(n, |end| n.min(end.max(0) as usize));
+                if n == 0 || n < start.unwrap_or_default().max(0) as usize {
+                    return None;
+                }
+                Some(&s[sign + n..])
+            },
+            "int",
+            "",
+        )
+    }
+
+    /// optional sign, integer part, optional fraction and optional exponent
+    /// eg "-1.5e10", ".5", "3." (as accepted by f64::from_str, excluding inf/nan)
+    fn float(self) -> Self {
+        apply(self, |s| float_len(s).map(|i| &s[i..]), "float", "")
+    }
+
+    /// an int or float token, classified by whether it has a "." or
+    /// exponent, eg "-12" or "1e3". Avoids trying int then backtracking to float
+    fn number(self) -> (Self, Option<Number>) {
+        self.log_inputs("number", "");
+        let Ok(s) = self.str() else {
+            return (self, None);
+        };
+        match float_len(s).map(|i| (&s[i..], s[..i].parse::<Number>())) {
+            Some((rest, Ok(n))) => {
+                let c = self.set_str(rest);
+                c.log_success_with_result("number", "", n);
+                (c, Some(n))
+            }
+            Some((_, Err(e))) => {
+                self.log_failure("number", "", &e);
+                (self.set_error(e), None)
+            }
+            None => {
+                let e = error::failure_static("number");
+                self.log_failure("number", "", &e);
+                (self.set_error(e), None)
+            }
+        }
+    }
+
+    /// matches the longest of the true or false words (ASCII case insensitive), as a
+    /// whole word: the char after it must not be alphanumeric, so "only" is not "on".
+    /// See boolean_default for the usual words
+    fn boolean(self, true_words: &[&str], false_words: &[&str]) -> (Self, Option<bool>) {
+        self.log_inputs("boolean", (true_words, false_words));
+        let Ok(s) = self.str() else {
+            return (self, None);
+        };
+        let longest = true_words
+            .iter()
+            .map(|w| (w, true))
+            .chain(false_words.iter().map(|w| (w, false)))
+            .filter_map(|(w, b)| strip_prefix_ignore_case(s, w).map(|rest| (rest, b)))
+            .filter(|(rest, _b)| !rest.starts_with(|c: char| c.is_alphanumeric()))
+            .min_by_key(|(rest, _b)| rest.len());
+        match longest {
+            Some((rest, b)) => {
+                let c = self.set_str(rest);
+                c.log_success_with_result("boolean", "", b);
+                (c, Some(b))
+            }
+            None => {
+                let words = [true_words, false_words].concat();
+                let e = error::failure("boolean", &format!("one of {words:?}"));
+                self.log_failure("boolean", "", &e);
+                (self.set_error(e), None)
+            }
+        }
+    }
+
+    /// boolean with the words ["true", "yes", "on", "1"] and ["false", "no", "off", "0"]
+    fn boolean_default(self) -> (Self, Option<bool>) {
+        self.boolean(&["true", "yes", "on", "1"], &["false", "no", "off", "0"])
+    }
+
+    /// an ISO 8601 date "YYYY-MM-DD", selected for parsing. The month and day are
+    /// range checked (with leap years), so "2023-13-01" and "2023-02-29" are a NoMatch
+    fn date_iso(self) -> Self {
+        apply(self, iso_date, "date_iso", "YYYY-MM-DD")
+    }
+
+    /// an ISO 8601 time "HH:MM:SS", optionally with a fraction of a second ".fff" and
+    /// a zone "Z" or "+HH:MM", selected for parsing. Each field is range checked
+    fn time_iso(self) -> Self {
+        apply(self, iso_time, "time_iso", "HH:MM:SS")
+    }
+
     /// alphanumeric or digit or hyphen (-)
     fn word(self) -> Self {
         apply(
@@ -624,26 +2229,199 @@ pub trait Matchable<'a>: Sized {
         )
     }
 
-    // TODO!
-    fn repeat<P, R: RangeBounds<i32> + Debug>(self, range: R, mut lexer: P) -> Self
+    /// runs the lexer repeatedly, at most range.end times, failing if fewer
+    /// than range.start iterations succeed
+    fn repeat<P, R: RangeBounds<i32> + Debug>(self, range: R, lexer: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        self.repeat_fold(range, lexer, (), |(), _s| ()).0
+    }
+
+    /// as repeat(), folding the text matched by each iteration into an accumulator
+    fn repeat_fold<P, R, B, F>(
+        self,
+        range: R,
+        mut lexer: P,
+        init: B,
+        mut fold: F,
+    ) -> (Self, Option<B>)
     where
         P: FnMut(Self) -> Self,
+        R: RangeBounds<i32> + Debug,
+        F: FnMut(B, &'a str) -> B,
         Self: Clone,
     {
+        let msg = "repeat";
+        self.log_inputs(msg, &range);
+        let Ok(mut s) = self.str() else {
+            return (self, None);
+        };
+        let (start, end) = start_end(&range);
+        let mut acc = init;
+        let mut count = 0;
         let mut cur = self;
-        for _i in 0..start_end(&range).1.unwrap_or(i32::MAX) {
-            let c = (lexer)(cur.clone());
+        while count < end.unwrap_or(i32::MAX) {
+            let c = branch(&cur, &mut lexer);
             match c.str() {
-                Ok(..) => cur = c,
-                Err(..) => return cur,
+                Ok(rest) => {
+                    let matched = s.get(..s.len().saturating_sub(rest.len())).unwrap_or("");
+                    acc = fold(acc, matched);
+                    cur = c;
+                    count += 1;
+                    if rest.len() == s.len() {
+                        // no progress: the lexer would match forever, so the minimum is met
+                        count = count.max(start.unwrap_or_default());
+                        break;
+                    }
+                    s = rest;
+                }
+                Err(..) => {
+                    if c.is_fatal() {
+                        let e = c.validate().err().unwrap_or_default();
+                        cur.log_failure(msg, &range, &e);
+                        return (cur.set_error(e), None);
+                    }
+                    break;
+                }
             }
         }
-        cur
+        if count < start.unwrap_or_default() {
+            let e = ParsingError::NoMatch {
+                action: msg,
+                args: "too few".into(),
+                offset: 0,
+            };
+            cur.log_failure(msg, &range, &e);
+            return (cur.set_error(e), None);
+        }
+        cur.log_success(msg, count);
+        (cur, Some(acc))
+    }
+
+    /// runs the parser, also returning the number of chars it consumed
+    /// eg to reject a numeric field of more than 9 digits.
+    /// A parser which leaves the cursor before where it started fails with NoMatch
+    fn measure<P>(self, mut parser: P) -> (Self, Option<usize>)
+    where
+        P: FnMut(Self) -> Self,
+    {
+        self.log_inputs("measure", "");
+        let Ok(before) = self.str() else {
+            return (self, None);
+        };
+        let c = parser(self);
+        match c.str() {
+            Ok(after) => match before.len().checked_sub(after.len()) {
+                Some(len) => {
+                    let n = before[..len].chars().count();
+                    c.log_success_with_result("measure", "", n);
+                    (c, Some(n))
+                }
+                // eg the parser restored an earlier checkpoint
+                None => {
+                    let e = ParsingError::NoMatch {
+                        action: "measure",
+                        args: "cursor moved backwards".into(),
+                        offset: 0,
+                    };
+                    c.log_failure("measure", "", &e);
+                    (c.set_error(e), None)
+                }
+            },
+            Err(_) => (c, None),
+        }
+    }
+
+    /// lookahead: runs the parser on a clone, leaving the cursor (and selection) unmoved
+    fn peek<P>(self, mut parser: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        let msg = "peek";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return self;
+        }
+        match parser(self.clone()).validate() {
+            Ok(..) => {
+                self.log_success(msg, "");
+                self
+            }
+            Err(e) => {
+                self.log_failure(msg, "", &e);
+                self.set_error(e)
+            }
+        }
+    }
+
+    /// negative lookahead: succeeds (without advancing) only if the parser fails.
+    /// A fatal error from the parser is propagated
+    fn not<P>(self, mut parser: P) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        let msg = "not";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return self;
+        }
+        let c = branch(&self, &mut parser);
+        if c.is_fatal() {
+            let e = c.validate().err().unwrap_or_default();
+            self.log_failure(msg, "", &e);
+            return self.set_error(e);
+        }
+        if c.is_skip() {
+            self.log_success(msg, "");
+            return self;
+        }
+        let e = error::failure_static(msg);
+        self.log_failure(msg, "", &e);
+        self.set_error(e)
+    }
+
+    /// tries each parser in turn from the current position, keeping the first success.
+    /// If all fail the last error is set. A fatal error stops any further alternatives
+    fn alt<P>(self, parsers: &mut [P]) -> Self
+    where
+        P: FnMut(Self) -> Self,
+        Self: Clone,
+    {
+        let msg = "alt";
+        self.log_inputs(msg, parsers.len());
+        if self.is_skip() {
+            return self;
+        }
+        let mut last = None;
+        for parser in parsers.iter_mut() {
+            let c = branch(&self, parser);
+            if c.str().is_ok() {
+                c.log_success(msg, "");
+                return c;
+            }
+            if c.is_fatal() {
+                let e = c.validate().err().unwrap_or_default();
+                self.log_failure(msg, "", &e);
+                return self.set_error(e);
+            }
+            last = Some(c);
+        }
+        // only the error of the last alternative is reported, so only it is formatted
+        let err = match last.map(|c| c.validate()) {
+            Some(Err(e)) => e,
+            _ => error::failure_static(msg),
+        };
+        self.log_failure(msg, "", &err);
+        self.set_error(err)
     }
 
     fn parse_struct_vec<P, T>(self, mut parser: P) -> (Self, Option<Vec<T>>)
     where
-        P: FnMut(&'a str) -> std::result::Result<(&'a str, T), ParsingError>,
+        P: FnMut(&'a str) -> core::result::Result<(&'a str, T), ParsingError>,
         Self: Clone,
         T: Debug,
         // C: SelectableCursor<'a>
@@ -651,15 +2429,14 @@ pub trait Matchable<'a>: Sized {
     {
         let mut vec = vec![];
         let Ok(mut str) = self.str() else {
-
-            return (self, None)
+            return (self, None);
         };
         loop {
             match (parser)(str) {
                 Ok((s, t)) => {
                     self.log_success_with_result(
                         "----> parse_struct_vec",
-                        std::any::type_name::<T>(),
+                        core::any::type_name::<T>(),
                         &t,
                     );
                     vec.push(t);
@@ -677,25 +2454,71 @@ pub trait Matchable<'a>: Sized {
         }
     }
 
-    fn parse_struct_vec_to<P, X, T>(self, mut parser: P, vec: &mut X) -> Result<Self, ParsingError>
+    /// like parse_struct_vec, but yields the structs one at a time without collecting them.
+    /// Iteration stops at the first NoMatch; other errors are yielded and end the iteration.
+    /// On a cursor already in error, that error is the only item
+    fn iter_struct<P, T>(self, parser: P) -> StructIter<'a, P>
+    where
+        P: FnMut(&'a str) -> core::result::Result<(&'a str, T), ParsingError>,
+    {
+        let cur = self.str().ok();
+        let err = match cur {
+            Some(_) => None,
+            None => self.validate().err(),
+        };
+        StructIter {
+            cur,
+            parser,
+            err,
+            done: false,
+        }
+    }
+
+    fn parse_struct_vec_to<P, X, T>(self, parser: P, vec: &mut X) -> Result<Self, ParsingError>
     where
-        P: FnMut(&'a str) -> std::result::Result<(&'a str, T), ParsingError>,
+        P: FnMut(&'a str) -> core::result::Result<(&'a str, T), ParsingError>,
         X: Extend<T>,
         Self: Clone,
         // A: IntoIterator<Item = T>
+    {
+        self.parse_struct_vec_to_min(parser, vec, 0)
+    }
+
+    /// as parse_struct_vec_to, but a NoMatch if fewer than min structs are parsed,
+    /// eg for a list which must not be empty
+    fn parse_struct_vec_to_min<P, X, T>(
+        self,
+        mut parser: P,
+        vec: &mut X,
+        min: usize,
+    ) -> Result<Self, ParsingError>
+    where
+        P: FnMut(&'a str) -> core::result::Result<(&'a str, T), ParsingError>,
+        X: Extend<T>,
+        Self: Clone,
     {
         let mut str = self.str()?;
+        let mut count = 0;
         loop {
             match (parser)(str) {
                 Ok((s, t)) => {
-                    vec.extend(std::iter::once(t));
+                    vec.extend(core::iter::once(t));
+                    count += 1;
                     str = s;
                 }
+                Err(ParsingError::NoMatch { .. }) if count < min => {
+                    // set_error locates the NoMatch where the list ended. Only running
+                    // out of a partial input may be cured by more input
+                    let e = match str.is_empty() && self.is_partial() {
+                        true => ParsingError::Incomplete { needed: 1 },
+                        false => count_mismatch("parse_struct_vec_to_min", min, usize::MAX, count),
+                    };
+                    let cur = self.set_str(str).set_error(e);
+                    return Err(cur.validate().err().expect("an errored cursor"));
+                }
                 Err(ParsingError::NoMatch { .. }) => return Ok(self.set_str(str)),
 
-                Err(ParsingError::Fatal(e)) => {
-                    return Err(ParsingError::Fatal(e));
-                }
+                Err(e) => return Err(e),
             }
         }
     }
@@ -703,7 +2526,7 @@ pub trait Matchable<'a>: Sized {
     #[deprecated(since = "0.0.3", note = "use function parse_with instead")]
     fn parse_with_str<P, T>(self, mut parser: P) -> (Self, Option<T>)
     where
-        P: FnMut(&str) -> std::result::Result<(&str, T), ParsingError>,
+        P: FnMut(&str) -> core::result::Result<(&str, T), ParsingError>,
     {
         if let Ok(s) = self.str() {
             if let Ok(outcome) = (parser)(s) {
@@ -715,31 +2538,6 @@ pub trait Matchable<'a>: Sized {
         (self, None)
     }
 
-    // fn parse_with<P, C, T>(self, parser: P) -> (Self, Option<T>)
-    // where
-    //     P: crate::parser::Parser<'a, C, T, Error=ParseError>,
-    //     Self::Cursor: Clone,
-    //     Self::Cursor: TryInto<C> + From<C>,
-    //     C: TryInto<&'a str>
-    //     // alternative:
-    //     // P: FnMut(C) -> Result<(C, T), ParseError>,
-    //     // Self::Cursor: Clone,
-    //     // Self::Cursor: TryInto<C> + From<C>,
-    //     // C: TryInto<&'a str>,
-    //     // C: TryFrom<&'a <Self as Matchable<'a>>::Cursor>,
-    //     // <Self as Matchable<'a>>::Cursor: 'a,
-    // {
-    //     if !self.is_skip() {
-    //         let res = crate::parser::invoke_parser(self.cursor().clone(), parser);
-    //         match res {
-    //             Ok((_c,t)) => (self, Some(t)),
-    //             Err(_e) => (self, None),
-    //         }
-    //     } else {
-    //         (self, None)
-    //     }
-    // }
-
     fn parse_opt_with<P, C, T>(self, mut parser: P) -> (Self, Option<Option<T>>)
     where
         P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
@@ -772,50 +2570,417 @@ pub trait Matchable<'a>: Sized {
         (self, None)
     }
 
+    /// runs a sub-parser from the cursor, appending its value to the tuple, eg
+    /// .parse_with(parse_clock) with parse_clock either a fn(&str) or fn(Cursor)
+    /// returning Result<(_, T), ParsingError>. Its error is set on the cursor
     fn parse_with<P, C, T>(self, mut parser: P) -> (Self, Option<T>)
     where
         P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
         Self::Cursor: Clone,
         Self::Cursor: TryInto<C> + From<C>,
         C: TryInto<&'a str>,
-        // alternative:
-        // P: FnMut(C) -> Result<(C, T), ParseError>,
-        // Self::Cursor: Clone,
-        // Self::Cursor: TryInto<C> + From<C>,
-        // C: TryInto<&'a str>,
-        // C: TryFrom<&'a <Self as Matchable<'a>>::Cursor>,
-        // <Self as Matchable<'a>>::Cursor: 'a,
     {
         if !self.is_skip() {
-            let res: Result<(C, T), ParsingError> = parser.parse(
-                self.cursor()
-                    .clone()
-                    .try_into()
-                    .unwrap_or_else(|_| panic!("Unexpected cursor() unwrap on valid cursor")),
-            );
-            return match res {
-                Ok((cur_c, t)) => match cur_c.try_into() {
-                    Ok(s) => (self.set_str(s), Some(t)),
-                    Err(_e) => (
-                        self.set_error(ParsingError::NoMatch {
-                            action: "",
-                            args: "",
-                        }),
-                        None,
-                    ),
-                },
+            return match run_parser(&self, &mut parser) {
+                Ok((s, t)) => (self.set_str(s), Some(t)),
                 Err(e) => (self.set_error(e), None),
             };
         }
         (self, None)
     }
 
-    // fn parse_with<P, C, T>(self, mut parser: P) -> (Self, Option<T>)
-    // where
-    //     P: FnMut(C) -> Result<(C, T), ParseError>,
-    //     Self::Cursor: Clone,
-    //     Self::Cursor: TryInto<C> + From<C>,
-    //     C: TryInto<&'a str>,
+    /// parses a T from the cursor using its Parse impl, eg `.parse_as::<Time>()`
+    fn parse_as<T>(self) -> (Self, Option<T>)
+    where
+        T: crate::parser::Parse<'a>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<Cursor<'a>> + From<Cursor<'a>>,
+    {
+        self.parse_with(|c: Cursor<'a>| T::parse(c))
+    }
+
+    /// optionally runs a value-producing parser: Some(t) on success, otherwise None
+    /// with the cursor left where it was. A fatal error is propagated
+    fn opt<P, C, T>(self, mut parser: P) -> (Self, Option<Option<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        if self.is_skip() {
+            return (self, None);
+        }
+        match run_parser(&self, &mut parser) {
+            Ok((s, t)) => (self.set_str(s), Some(Some(t))),
+            Err(e) if e.is_recoverable() => (self, Some(None)),
+            Err(e) => (self.set_error(e), None),
+        }
+    }
+
+    /// runs a value-producing parser, returning its result rather than propagating it.
+    /// On a recoverable failure the cursor is left where it was (and not in error),
+    /// so the caller can inspect the error and carry on. A fatal error is also set on the cursor
+    fn attempt<P, C, T>(self, mut parser: P) -> (Self, Result<T, ParsingError>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        if let Err(e) = self.str() {
+            return (self, Err(e));
+        }
+        match run_parser(&self, &mut parser) {
+            Ok((s, t)) => (self.set_str(s), Ok(t)),
+            Err(e) if e.is_recoverable() => (self, Err(e)),
+            Err(e) => (self.set_error(e.clone()), Err(e)),
+        }
+    }
+
+    /// one or more operands separated by operators, combined left to right,
+    /// so "1-2-3" is combine(combine(1, '-', 2), '-', 3). Stops before an operator
+    /// not followed by an operand
+    fn left_assoc<P, O, F, C, T, Op>(
+        self,
+        mut operand: P,
+        mut op: O,
+        mut combine: F,
+    ) -> (Self, Option<T>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        O: crate::parser::Parser<'a, C, Op, Error = ParsingError>,
+        F: FnMut(T, Op, T) -> T,
+        Self: Clone,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "left_assoc";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return (self, None);
+        }
+        let (s, mut acc) = match run_parser(&self, &mut operand) {
+            Ok(st) => st,
+            Err(e) => {
+                self.log_failure(msg, "", &e);
+                return (self.set_error(e), None);
+            }
+        };
+        let mut cur = self.set_str(s);
+        loop {
+            let (c, o) = match run_parser(&cur, &mut op) {
+                Ok((s, o)) => (cur.clone().set_str(s), o),
+                Err(e) if e.is_recoverable() => break,
+                Err(e) => return (cur.set_error(e), None),
+            };
+            match run_parser(&c, &mut operand) {
+                Ok((s, t)) => {
+                    acc = combine(acc, o, t);
+                    cur = c.set_str(s);
+                }
+                Err(e) if e.is_recoverable() => break,
+                Err(e) => return (c.set_error(e), None),
+            }
+        }
+        cur.log_success(msg, "");
+        (cur, Some(acc))
+    }
+
+    /// like opt, but yielding default (with the cursor left where it was) on a
+    /// recoverable failure, eg an optional ":ss" defaulting to 0. A fatal error is propagated
+    fn or_value<P, C, T>(self, parser: P, default: T) -> (Self, Option<T>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        match self.opt(parser) {
+            (c, Some(t)) => (c, Some(t.unwrap_or(default))),
+            (c, None) => (c, None),
+        }
+    }
+
+    /// open, then the value parsed by inner, then close, eg "{" value "}".
+    /// A failure to match open or close is reported as such, a fatal error
+    /// from either is passed on unchanged
+    fn delimited<O, P, Q, C, T>(self, mut open: O, mut inner: P, mut close: Q) -> (Self, Option<T>)
+    where
+        O: FnMut(Self) -> Self,
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Q: FnMut(Self) -> Self,
+        Self: Clone,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "delimited";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return (self, None);
+        }
+        let missing = |args: &'static str| ParsingError::NoMatch {
+            action: msg,
+            args: args.into(),
+            offset: 0,
+        };
+        let c = open(self.clone());
+        if c.is_fatal() {
+            let e = c.validate().err().unwrap_or_default();
+            return (self.set_error(e), None);
+        }
+        if c.is_skip() {
+            return (self.set_error(missing("open")), None);
+        }
+        let (c, t) = match run_parser(&c, &mut inner) {
+            Ok((s, t)) => (c.set_str(s), t),
+            Err(e) => return (c.set_error(e), None),
+        };
+        let end = close(c.clone());
+        if end.is_fatal() {
+            let e = end.validate().err().unwrap_or_default();
+            return (c.set_error(e), None);
+        }
+        if end.is_skip() {
+            return (c.set_error(missing("close")), None);
+        }
+        end.log_success(msg, "");
+        (end, Some(t))
+    }
+
+    /// prefix, then the value parsed by value, eg "$" amount
+    fn preceded<O, P, C, T>(self, mut prefix: O, value: P) -> (Self, Option<T>)
+    where
+        O: FnMut(Self) -> Self,
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        prefix(self).parse_with(value)
+    }
+
+    /// the value parsed by value, then suffix, eg amount "%"
+    fn terminated<P, Q, C, T>(self, value: P, mut suffix: Q) -> (Self, Option<T>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Q: FnMut(Self) -> Self,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let (c, t) = self.parse_with(value);
+        if c.is_skip() {
+            return (c, None);
+        }
+        let c = suffix(c);
+        if c.is_skip() {
+            return (c, None);
+        }
+        (c, t)
+    }
+
+    /// one or more items separated by sep, eg "1, 2, 3". No trailing separator is consumed.
+    /// If the first item doesn't match, an empty vec is returned
+    fn separated_list<P, S, C, T>(self, mut item: P, mut sep: S) -> (Self, Option<Vec<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        S: FnMut(Self) -> Self,
+        Self: Clone,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "separated_list";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return (self, None);
+        }
+        let (cur, vec) = separated(self, &mut item, &mut sep);
+        if let Some(vec) = &vec {
+            cur.log_success(msg, vec.len());
+        }
+        (cur, vec)
+    }
+
+    /// zero or more items separated by sep, with trailing deciding whether a separator
+    /// after the last item is allowed, forbidden or required. Where it is forbidden, the
+    /// error is that of the item expected after the separator
+    fn list<P, S, C, T>(self, mut item: P, mut sep: S, trailing: Trailing) -> (Self, Option<Vec<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        S: FnMut(Self) -> Self,
+        Self: Clone,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "list";
+        self.log_inputs(msg, trailing);
+        if self.is_skip() {
+            return (self, None);
+        }
+        let (mut cur, vec) = match separated(self, &mut item, &mut sep) {
+            (cur, Some(vec)) => (cur, vec),
+            (cur, None) => return (cur, None),
+        };
+        if !vec.is_empty() {
+            let after_sep = branch(&cur, &mut sep);
+            if after_sep.is_fatal() {
+                return (after_sep, None);
+            }
+            let consumed = match (cur.str(), after_sep.str()) {
+                (Ok(before), Ok(after)) => before.len() > after.len(),
+                _ => false,
+            };
+            let e = match (trailing, after_sep.is_skip()) {
+                (Trailing::Require, true) => Some(error::failure(msg, "separator after last item")),
+                // the list ended at a separator, so the item after it failed: say why
+                (Trailing::Forbid, false) if consumed => {
+                    let e = run_parser(&after_sep, &mut item).err();
+                    cur = after_sep;
+                    Some(e.unwrap_or_else(|| error::failure(msg, "trailing separator")))
+                }
+                (Trailing::Allow | Trailing::Require, false) => {
+                    cur = after_sep;
+                    None
+                }
+                _ => None,
+            };
+            if let Some(e) = e {
+                cur.log_failure(msg, trailing, &e);
+                return (cur.set_error(e), None);
+            }
+        }
+        cur.log_success(msg, vec.len());
+        (cur, Some(vec))
+    }
+
+    /// collects items until terminator matches (consuming it), eg statements up to "end".
+    /// The terminator is tried first, so a NoMatch (from neither matching) reads
+    /// "item or terminator". A fatal error is propagated
+    fn repeat_until<P, Q, C, T>(self, mut item: P, mut terminator: Q) -> (Self, Option<Vec<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Q: FnMut(Self) -> Self,
+        Self: Clone,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "repeat_until";
+        self.log_inputs(msg, "");
+        if self.is_skip() {
+            return (self, None);
+        }
+        let mut vec = vec![];
+        let mut cur = self;
+        loop {
+            let end = branch(&cur, &mut terminator);
+            if !end.is_skip() {
+                end.log_success(msg, vec.len());
+                return (end, Some(vec));
+            }
+            match run_parser(&cur, &mut item) {
+                Ok((s, t)) if cur.str().map_or(false, |before| before.len() > s.len()) => {
+                    vec.push(t);
+                    cur = cur.set_str(s);
+                }
+                // an item matching without consuming anything would never reach the terminator
+                Ok(_) => break,
+                Err(e) if e.is_recoverable() => break,
+                Err(e) => {
+                    cur.log_failure(msg, "", &e);
+                    return (cur.set_error(e), None);
+                }
+            }
+        }
+        let e = error::failure(msg, "item or terminator");
+        cur.log_failure(msg, "", &e);
+        (cur.set_error(e), None)
+    }
+
+    /// collects between start and end items parsed by item (like nom's many_m_n).
+    /// NoMatch if fewer than start items are found. A fatal error is propagated
+    fn many<R, P, C, T>(self, range: R, mut item: P) -> (Self, Option<Vec<T>>)
+    where
+        R: RangeBounds<i32> + Debug,
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        let msg = "many";
+        self.log_inputs(msg, &range);
+        if self.is_skip() {
+            return (self, None);
+        }
+        let (start, end) = start_end(&range);
+        let start = start.unwrap_or_default().max(0) as usize;
+        let end = end.map_or(usize::MAX, |end| end.max(0) as usize);
+        let mut vec = vec![];
+        let mut cur = self;
+        while vec.len() < end {
+            match run_parser(&cur, &mut item) {
+                Ok((s, t)) => {
+                    vec.push(t);
+                    let no_progress = cur.str().map_or(true, |before| before.len() == s.len());
+                    cur = cur.set_str(s);
+                    if no_progress {
+                        // the item would match forever without consuming anything
+                        break;
+                    }
+                }
+                Err(e) if e.is_recoverable() => break,
+                Err(e) => {
+                    cur.log_failure(msg, &range, &e);
+                    return (cur.set_error(e), None);
+                }
+            }
+        }
+        if vec.len() < start {
+            let e = ParsingError::NoMatch {
+                action: msg,
+                args: "too few".into(),
+                offset: 0,
+            };
+            cur.log_failure(msg, &range, &e);
+            return (cur.set_error(e), None);
+        }
+        cur.log_success(msg, vec.len());
+        (cur, Some(vec))
+    }
+
+    /// zero or more items, see many
+    fn many0<P, C, T>(self, item: P) -> (Self, Option<Vec<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        self.many(0.., item)
+    }
+
+    /// one or more items, see many. NoMatch if there are none
+    fn many1<P, C, T>(self, item: P) -> (Self, Option<Vec<T>>)
+    where
+        P: crate::parser::Parser<'a, C, T, Error = ParsingError>,
+        Self::Cursor: Clone,
+        Self::Cursor: TryInto<C> + From<C>,
+        C: TryInto<&'a str>,
+    {
+        self.many(1.., item)
+    }
+
+    // fn parse_with<P, C, T>(self, mut parser: P) -> (Self, Option<T>)
+    // where
+    //     P: FnMut(C) -> Result<(C, T), ParseError>,
+    //     Self::Cursor: Clone,
+    //     Self::Cursor: TryInto<C> + From<C>,
+    //     C: TryInto<&'a str>,
     //     // alternative:
     //     // P: FnMut(C) -> Result<(C, T), ParseError>,
     //     // Self::Cursor: Clone,
@@ -862,7 +3027,7 @@ pub trait Matchable<'a>: Sized {
 
     // fn parse_put<P, T>(self, mut parser: P, dest: &mut T) -> Result<Self, ParseError>
     // where
-    //     P: FnMut(&str) -> std::result::Result<(&str, T), ParseError>,
+    //     P: FnMut(&str) -> core::result::Result<(&str, T), ParseError>,
     // {
     //     let s: &str = self.str()?;
     //     let outcome = (parser)(s)?;
@@ -873,7 +3038,7 @@ pub trait Matchable<'a>: Sized {
 
     // fn parse_to_opt<P, T>(self, mut parser: P, dest: &mut Option<T>) -> Result<Self, ParseError>
     // where
-    //     P: FnMut(&str) -> std::result::Result<(&str, T), ParseError>,
+    //     P: FnMut(&str) -> core::result::Result<(&str, T), ParseError>,
     // {
     //     let s: &str = self.str()?;
     //     let outcome = (parser)(s)?;
@@ -907,7 +3072,7 @@ impl<'a> Matchable<'a> for Option<&'a str> {
 
     #[inline]
     fn str(&self) -> Result<&'a str, ParsingError> {
-        self.ok_or_else(|| error::failure("str on erroring cursor", ""))
+        self.ok_or_else(|| error::failure_static("str on erroring cursor"))
     }
 
     #[inline]
@@ -961,6 +3126,104 @@ impl<'a> Matchable<'a> for Option<&'a str> {
 // }
 
 impl<'a> Selectable<'a> for Cursor<'a> {
+    fn offset(&self) -> usize {
+        self.byte_offset()
+    }
+
+    fn selection_range(&self) -> Result<core::ops::Range<usize>, ParsingError> {
+        let Some(cur) = self.cur else {
+            return Err(self.error().unwrap_or_default());
+        };
+        // selections are always suffixes of the original input
+        let (s, e) = self.selection.selection(cur);
+        let len = self.context.len();
+        Ok(len - s.len()..len - e.len())
+    }
+
+    fn trim_selection(mut self) -> Self {
+        self.log_inputs("trim_selection", "");
+        let Some(cur) = self.cur else {
+            return self;
+        };
+        let (s, e) = self.selection.selection(cur);
+        let text = &s[..s.len() - e.len()];
+        let start = &s[text.len()
+            - text
+                .trim_start_matches(|c: char| c.is_ascii_whitespace())
+                .len()..];
+        let trimmed = text.trim_matches(|c: char| c.is_ascii_whitespace());
+        let end = &start[trimmed.len()..];
+        self.selection = match self.selection {
+            Selection::Start(..) => Selection::Start(start, Some(end)),
+            _ => Selection::Last(start, end),
+        };
+        self.log_success("trim_selection", trimmed);
+        self
+    }
+
+    fn mark(mut self, name: &'static str) -> Self {
+        self.log_inputs("mark", name);
+        if let Some(cur) = self.cur {
+            let names = self.names.to_mut();
+            names.retain(|(n, _, _)| *n != name);
+            names.push((name, cur, None));
+            self.log_success("mark", name);
+        }
+        self
+    }
+
+    fn capture_to(mut self, name: &'static str) -> Self {
+        self.log_inputs("capture_to", name);
+        let Some(cur) = self.cur else {
+            return self;
+        };
+        match self.names.iter().position(|(n, _, _)| *n == name) {
+            Some(i) => {
+                self.names.to_mut()[i].2 = Some(cur);
+                self.log_success("capture_to", name);
+                self
+            }
+            None => {
+                let e = error::failure("capture_to", name);
+                self.log_failure("capture_to", name, &e);
+                self.set_error(e)
+            }
+        }
+    }
+
+    fn errors(&self) -> &[ParsingError] {
+        &self.errors
+    }
+
+    fn push_error(mut self, e: ParsingError) -> Self {
+        if self.cur.is_some() {
+            self.errors.to_mut().push(e);
+        }
+        self
+    }
+
+    fn named(&self, name: &str) -> Result<&'a str, ParsingError> {
+        if let Some(e) = self.error() {
+            return Err(e);
+        }
+        match self.names.iter().find(|(n, _, _)| *n == name) {
+            Some((_, s, Some(e))) => Ok(&s[..s.len() - e.len()]),
+            _ => Err(ParsingError::NoMatch {
+                action: "named",
+                args: "unknown name".into(),
+                offset: self.byte_offset(),
+            }),
+        }
+    }
+
+    fn line_col(&self) -> (usize, usize) {
+        self.line_and_col()
+    }
+
+    fn column(&self) -> usize {
+        self.col()
+    }
+
     fn get_selection(&self) -> Result<&'a str, ParsingError> {
         self.log_inputs("get_selection", "");
         if let Some(cur) = self.cur {
@@ -969,7 +3232,7 @@ impl<'a> Selectable<'a> for Cursor<'a> {
             self.log_success("get_selection", &s[..len]);
             return Ok(&s[..len]);
         }
-        Err(self.clone().err.unwrap())
+        Err(self.error().unwrap_or_default())
     }
 
     fn selection_start(self) -> Self {
@@ -980,8 +3243,13 @@ impl<'a> Selectable<'a> for Cursor<'a> {
                 selection: Selection::Start(cur, None),
                 err: self.err,
                 context: self.context,
+                names: self.names,
+                errors: self.errors,
+                partial: self.partial,
+                committed: self.committed,
+                label: self.label,
             };
-            cur.log_success("selection_end", "");
+            cur.log_success("selection_start", "");
             cur
         } else {
             self
@@ -996,6 +3264,11 @@ impl<'a> Selectable<'a> for Cursor<'a> {
                 selection: Selection::Start(self.selection.start(), self.cur),
                 err: self.err,
                 context: self.context,
+                names: self.names,
+                errors: self.errors,
+                partial: self.partial,
+                committed: self.committed,
+                label: self.label,
             };
             cur.log_success("selection_end", "");
             cur
@@ -1018,24 +3291,104 @@ impl<'a> Matchable<'a> for Cursor<'a> {
         self
     }
 
+    fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    fn label(&self) -> &'static str {
+        self.label
+    }
+
+    fn set_label(mut self, label: &'static str) -> Self {
+        if self.cur.is_some() {
+            self.label = label;
+        }
+        self
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
+        let selection = match (self.selection, self.cur) {
+            // the last match runs from the previous cursor position (which may
+            // differ from the end of the selection, if trimmed)
+            (Selection::Last(..), Some(prev)) => Selection::Last(prev, s),
+            (selection, _) => selection.move_cursor(s),
+        };
         Self {
-            selection: self.selection.move_cursor(s),
+            selection,
             cur: self.cur.set_str(s),
             err: self.err,
             context: self.context,
+            names: self.names,
+            errors: self.errors,
+            partial: self.partial,
+            committed: self.committed,
+            label: self.label,
         }
     }
 
     #[inline]
     fn set_error(self, e: ParsingError) -> Self {
+        let e = match self.cur {
+            Some(_) => e.at(self.byte_offset()),
+            None => e,
+        };
+        let e = match e {
+            ParsingError::NoMatch { .. } if self.committed && self.cur.is_some() => {
+                ParsingError::Fatal(Some(Box::new(e)))
+            }
+            e => e,
+        };
         Self {
             selection: self.selection,
             cur: None,
-            err: Some(e),
+            err: Some(Failure::Error(e)),
             context: self.context,
+            names: self.names,
+            errors: self.errors,
+            partial: self.partial,
+            committed: false,
+            label: self.label,
+        }
+    }
+
+    fn set_no_match(self, action: &'static str, args: &str) -> Self {
+        match InlineStr::new(args) {
+            Some(args) if self.cur.is_some() && !self.committed => Self {
+                err: Some(Failure::NoMatch {
+                    action,
+                    args,
+                    offset: self.byte_offset(),
+                }),
+                cur: None,
+                ..self
+            },
+            _ => {
+                let found = self.cur.unwrap_or_default();
+                let e = error::failure(action, &error::with_found(args, found));
+                self.set_error(e)
+            }
+        }
+    }
+
+    fn is_fatal(&self) -> bool {
+        matches!(&self.err, Some(Failure::Error(e)) if !e.is_recoverable())
+    }
+
+    fn commit(self) -> Self {
+        self.log_inputs("commit", "");
+        self.set_committed(true)
+    }
+
+    fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    fn set_committed(mut self, committed: bool) -> Self {
+        if self.cur.is_some() {
+            self.committed = committed;
         }
+        self
     }
 
     // #[inline]
@@ -1043,7 +3396,7 @@ impl<'a> Matchable<'a> for Cursor<'a> {
     // }
 
     fn validate(self) -> Result<Self::DeTuple, ParsingError> {
-        match self.err {
+        match self.error() {
             None => Ok(self.str()?),
             Some(e) => Err(e),
         }
@@ -1055,29 +3408,93 @@ impl<'a, T> Selectable<'a> for (Cursor<'a>, Option<T>) {
         self.0.get_selection()
     }
 
-    fn selection_start(self) -> Self {
-        (self.0.selection_start(), self.1)
+    fn offset(&self) -> usize {
+        self.0.offset()
     }
 
-    fn selection_end(self) -> Self {
-        (self.0.selection_end(), self.1)
+    fn selection_range(&self) -> Result<core::ops::Range<usize>, ParsingError> {
+        self.0.selection_range()
     }
-}
 
-impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
-    type Cursor = Cursor<'a>;
-    type DeTuple = (&'a str, T);
+    fn line_col(&self) -> (usize, usize) {
+        self.0.line_col()
+    }
 
-    #[inline]
-    fn str(&self) -> Result<&'a str, ParsingError> {
-        self.0.str()
+    fn column(&self) -> usize {
+        self.0.column()
     }
 
-    fn cursor(&self) -> &Self::Cursor {
-        &self.0
+    fn trim_selection(self) -> Self {
+        (self.0.trim_selection(), self.1)
     }
 
-    #[inline]
+    fn mark(self, name: &'static str) -> Self {
+        (self.0.mark(name), self.1)
+    }
+
+    fn capture_to(self, name: &'static str) -> Self {
+        (self.0.capture_to(name), self.1)
+    }
+
+    fn named(&self, name: &str) -> Result<&'a str, ParsingError> {
+        self.0.named(name)
+    }
+
+    fn errors(&self) -> &[ParsingError] {
+        self.0.errors()
+    }
+
+    fn push_error(self, e: ParsingError) -> Self {
+        (self.0.push_error(e), self.1)
+    }
+
+    fn selection_start(self) -> Self {
+        (self.0.selection_start(), self.1)
+    }
+
+    fn selection_end(self) -> Self {
+        (self.0.selection_end(), self.1)
+    }
+}
+
+impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
+    type Cursor = Cursor<'a>;
+    type DeTuple = (&'a str, T);
+
+    #[inline]
+    fn str(&self) -> Result<&'a str, ParsingError> {
+        self.0.str()
+    }
+
+    fn cursor(&self) -> &Self::Cursor {
+        &self.0
+    }
+
+    fn is_partial(&self) -> bool {
+        self.0.is_partial()
+    }
+
+    fn label(&self) -> &'static str {
+        self.0.label()
+    }
+
+    fn set_label(self, label: &'static str) -> Self {
+        (self.0.set_label(label), self.1)
+    }
+
+    fn commit(self) -> Self {
+        (self.0.commit(), self.1)
+    }
+
+    fn is_committed(&self) -> bool {
+        self.0.is_committed()
+    }
+
+    fn set_committed(self, committed: bool) -> Self {
+        (self.0.set_committed(committed), self.1)
+    }
+
+    #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
     }
@@ -1087,6 +3504,14 @@ impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
         (self.0.set_error(e), self.1)
     }
 
+    fn set_no_match(self, action: &'static str, args: &str) -> Self {
+        (self.0.set_no_match(action, args), self.1)
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.0.is_fatal()
+    }
+
     // #[inline]
     // fn validate(self) -> Result<Self, ParseError> {
     //     self.0.validate().map(|c| (c, self.1))
@@ -1096,12 +3521,13 @@ impl<'a, T> Matchable<'a> for (Cursor<'a>, Option<T>) {
     fn validate(self) -> Result<Self::DeTuple, ParsingError> {
         let e = ParsingError::NoMatch {
             action: "validate",
-            args: "",
+            args: "".into(),
+            offset: 0,
         };
         if self.0.err.is_none() {
             self.log_success_with_result(
                 "----> validate",
-                std::any::type_name::<Self::DeTuple>(),
+                core::any::type_name::<Self::DeTuple>(),
                 "",
             );
         }
@@ -1126,6 +3552,30 @@ impl<'a, T1, T2> Matchable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
         &self.0 .0
     }
 
+    fn is_partial(&self) -> bool {
+        self.0.is_partial()
+    }
+
+    fn label(&self) -> &'static str {
+        self.0.label()
+    }
+
+    fn set_label(self, label: &'static str) -> Self {
+        (self.0.set_label(label), self.1)
+    }
+
+    fn commit(self) -> Self {
+        (self.0.commit(), self.1)
+    }
+
+    fn is_committed(&self) -> bool {
+        self.0.is_committed()
+    }
+
+    fn set_committed(self, committed: bool) -> Self {
+        (self.0.set_committed(committed), self.1)
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
@@ -1136,6 +3586,14 @@ impl<'a, T1, T2> Matchable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
         (self.0.set_error(e), self.1)
     }
 
+    fn set_no_match(self, action: &'static str, args: &str) -> Self {
+        (self.0.set_no_match(action, args), self.1)
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.0.is_fatal()
+    }
+
     // #[inline]
     // fn validate(self) -> Result<Self, ParseError> {
     //     self.0.validate().map(|c| (c, self.1))
@@ -1145,7 +3603,8 @@ impl<'a, T1, T2> Matchable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
     fn validate(self) -> Result<Self::DeTuple, ParsingError> {
         let e = ParsingError::NoMatch {
             action: "validate",
-            args: "",
+            args: "".into(),
+            offset: 0,
         };
         let r = self.0.validate()?;
         Ok((r.0, r.1, self.1.ok_or(e)?))
@@ -1165,6 +3624,30 @@ impl<'a, T1, T2, T3> Matchable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
         &self.0 .0 .0
     }
 
+    fn is_partial(&self) -> bool {
+        self.0.is_partial()
+    }
+
+    fn label(&self) -> &'static str {
+        self.0.label()
+    }
+
+    fn set_label(self, label: &'static str) -> Self {
+        (self.0.set_label(label), self.1)
+    }
+
+    fn commit(self) -> Self {
+        (self.0.commit(), self.1)
+    }
+
+    fn is_committed(&self) -> bool {
+        self.0.is_committed()
+    }
+
+    fn set_committed(self, committed: bool) -> Self {
+        (self.0.set_committed(committed), self.1)
+    }
+
     #[inline]
     fn set_str(self, s: &'a str) -> Self {
         (self.0.set_str(s), self.1)
@@ -1175,6 +3658,14 @@ impl<'a, T1, T2, T3> Matchable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
         (self.0.set_error(e), self.1)
     }
 
+    fn set_no_match(self, action: &'static str, args: &str) -> Self {
+        (self.0.set_no_match(action, args), self.1)
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.0.is_fatal()
+    }
+
     // #[inline]
     // fn validate(self) -> Result<Self, ParseError> {
     //     self.0.validate().map(|c| (c, self.1))
@@ -1184,7 +3675,8 @@ impl<'a, T1, T2, T3> Matchable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
     fn validate(self) -> Result<Self::DeTuple, ParsingError> {
         let e3 = ParsingError::NoMatch {
             action: "validate",
-            args: "",
+            args: "".into(),
+            offset: 0,
         };
         let (c, t1, t2) = self.0.validate()?;
         Ok((c, t1, t2, self.1.ok_or(e3)?))
@@ -1196,6 +3688,46 @@ impl<'a, T1, T2> Selectable<'a> for ((Cursor<'a>, Option<T1>), Option<T2>) {
         self.0.get_selection()
     }
 
+    fn offset(&self) -> usize {
+        self.0.offset()
+    }
+
+    fn selection_range(&self) -> Result<core::ops::Range<usize>, ParsingError> {
+        self.0.selection_range()
+    }
+
+    fn line_col(&self) -> (usize, usize) {
+        self.0.line_col()
+    }
+
+    fn column(&self) -> usize {
+        self.0.column()
+    }
+
+    fn trim_selection(self) -> Self {
+        (self.0.trim_selection(), self.1)
+    }
+
+    fn mark(self, name: &'static str) -> Self {
+        (self.0.mark(name), self.1)
+    }
+
+    fn capture_to(self, name: &'static str) -> Self {
+        (self.0.capture_to(name), self.1)
+    }
+
+    fn named(&self, name: &str) -> Result<&'a str, ParsingError> {
+        self.0.named(name)
+    }
+
+    fn errors(&self) -> &[ParsingError] {
+        self.0.errors()
+    }
+
+    fn push_error(self, e: ParsingError) -> Self {
+        (self.0.push_error(e), self.1)
+    }
+
     fn selection_start(self) -> Self {
         (self.0.selection_start(), self.1)
     }
@@ -1210,6 +3742,46 @@ impl<'a, T1, T2, T3> Selectable<'a> for (((Cursor<'a>, Option<T1>), Option<T2>),
         self.0.get_selection()
     }
 
+    fn offset(&self) -> usize {
+        self.0.offset()
+    }
+
+    fn selection_range(&self) -> Result<core::ops::Range<usize>, ParsingError> {
+        self.0.selection_range()
+    }
+
+    fn line_col(&self) -> (usize, usize) {
+        self.0.line_col()
+    }
+
+    fn column(&self) -> usize {
+        self.0.column()
+    }
+
+    fn trim_selection(self) -> Self {
+        (self.0.trim_selection(), self.1)
+    }
+
+    fn mark(self, name: &'static str) -> Self {
+        (self.0.mark(name), self.1)
+    }
+
+    fn capture_to(self, name: &'static str) -> Self {
+        (self.0.capture_to(name), self.1)
+    }
+
+    fn named(&self, name: &str) -> Result<&'a str, ParsingError> {
+        self.0.named(name)
+    }
+
+    fn errors(&self) -> &[ParsingError] {
+        self.0.errors()
+    }
+
+    fn push_error(self, e: ParsingError) -> Self {
+        (self.0.push_error(e), self.1)
+    }
+
     fn selection_start(self) -> Self {
         (self.0.selection_start(), self.1)
     }
@@ -1224,7 +3796,8 @@ mod tests {
 
     use std::ops::RangeBounds;
 
-    use crate::text_parser::{Bind, ParsingError, Selectable};
+    use crate::parser::Parse;
+    use crate::text_parser::{AndThen, Bind, MapValue, Number, ParsingError, Selectable, Trailing};
 
     use super::{Cursor, Matchable};
     use test_log::test;
@@ -1317,6 +3890,18 @@ mod tests {
         Ok((c, Time(hh, mm, sss)))
     }
 
+    fn int(s: &str) -> Result<(&str, i32), ParsingError> {
+        Cursor::from(s).digits(1..).parse_selection().validate()
+    }
+
+    fn int_ws(s: &str) -> Result<(&str, i32), ParsingError> {
+        Cursor::from(s).parse_with(int).ws().validate()
+    }
+
+    fn ws_int(s: &str) -> Result<(&str, i32), ParsingError> {
+        Cursor::from(s).ws().parse_with(int).validate()
+    }
+
     #[test]
     fn test_parse_from_str() {
         let (c, i, j) = Cursor::from("42X45Y")
@@ -1499,6 +4084,2160 @@ mod tests {
         assert_eq!(res.1.len(), 3);
         assert_eq!(res.0, "");
     }
+
+    #[test]
+    fn test_float() {
+        assert_eq!(Some("-1.5e10X").float(), Some("X"));
+        assert_eq!(Some("+2.5E-3 ").float(), Some(" "));
+        assert_eq!(Some(".5").float(), Some(""));
+        assert_eq!(Some("3.").float(), Some(""));
+        assert_eq!(Some("42").float(), Some(""));
+        assert_eq!(Some("1e").float(), Some("e"));
+        assert_eq!(Some("1e+").float(), Some("e+"));
+        assert_eq!(Some("-").float(), None);
+        assert_eq!(Some(".").float(), None);
+        assert_eq!(Some("-.e5").float(), None);
+        assert_eq!(Some("").float(), None);
+        assert_eq!(Some("X1.0").float(), None);
+        assert_eq!(None.float(), None);
+
+        let (c, f) = Cursor::from("-1.5e10,")
+            .float()
+            .parse_selection::<f64>()
+            .validate()
+            .unwrap();
+        assert_eq!(f, -1.5e10);
+        assert_eq!(c, ",");
+    }
+
+    #[test]
+    fn test_peek() {
+        assert_eq!(Some("Hello").peek(|c| c.text("He")), Some("Hello"));
+        assert_eq!(Some("Hello").peek(|c| c.text("Bye")), None);
+        assert_eq!(None.peek(|c| c.text("He")), None);
+
+        let c = Cursor::from("12ab").selection_start().digits(1..);
+        let peeked = c
+            .clone()
+            .peek(|c| c.selection_start().alphabetics(1..).selection_end());
+        assert_eq!(peeked.str().unwrap(), c.str().unwrap());
+        assert_eq!(peeked.get_selection().unwrap(), c.get_selection().unwrap());
+        assert_eq!(peeked, c);
+
+        let c = Cursor::from("12ab").peek(|c| c.alphabetics(1..));
+        assert!(c.validate().is_err());
+    }
+
+    #[test]
+    fn test_not() {
+        assert_eq!(Some("Hello").not(|c| c.text("Bye")), Some("Hello"));
+        assert_eq!(Some("Hello").not(|c| c.text("He")), None);
+        assert_eq!(None.not(|c| c.text("He")), None);
+
+        // everything up to (but not past) the keyword "end"
+        let (c, body) = Cursor::from("a b c end")
+            .selection_start()
+            .repeat(0.., |c| c.not(|c| c.text("end")).chars_any(1..=1))
+            .selection_end()
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!(body, "a b c ");
+        assert_eq!(c, "end");
+
+        let c = Cursor::from("abc").not(|c| c.set_error(ParsingError::Fatal(None)));
+        assert!(matches!(c.validate(), Err(ParsingError::Fatal(..))));
+    }
+
+    #[test]
+    fn test_text_ignore_case() {
+        assert_eq!(Some("pm X").text_ignore_case("PM"), Some(" X"));
+        assert_eq!(Some("Pm").text_ignore_case("pM"), Some(""));
+        assert_eq!(Some("P").text_ignore_case("PM"), None);
+        assert_eq!(Some("AM").text_ignore_case("PM"), None);
+        assert_eq!(Some("ÉA").text_ignore_case("éa"), None);
+        assert_eq!(Some("éA").text_ignore_case("éa"), Some(""));
+        assert_eq!(Some("pm").text_alt_ignore_case(&["AM", "PM"]), Some(""));
+        assert_eq!(Some("xm").text_alt_ignore_case(&["AM", "PM"]), None);
+
+        let (_c, ampm) = Cursor::from("pm")
+            .text_alt_ignore_case(&["AM", "PM"])
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!(ampm, "pm");
+    }
+
+    #[test]
+    fn test_offset_line_col() {
+        let c = Cursor::from("ab\ncd\nxyz");
+        assert_eq!(c.offset(), 0);
+        assert_eq!(c.line_col(), (1, 1));
+
+        let c = c.scan_eol().scan_eol().text("xy");
+        assert_eq!(c.offset(), 8);
+        assert_eq!(c.line_col(), (3, 3));
+
+        let c = Cursor::from("£1\n£").text("£1").parse_selection::<String>();
+        assert_eq!(c.offset(), 3);
+        assert_eq!(c.line_col(), (1, 3));
+    }
+
+    #[test]
+    fn test_error_offset() {
+        let e = Cursor::from("12:3X")
+            .digits(2..=2)
+            .text(":")
+            .text("45")
+            .validate()
+            .unwrap_err();
+        assert!(matches!(e, ParsingError::NoMatch { offset: 3, .. }));
+        assert!(e.to_string().ends_with("at byte 3"), "{e}");
+    }
+
+    #[test]
+    fn test_quoted() {
+        let c = Cursor::from(r#""a\"b" rest"#).quoted('"', '\\');
+        assert_eq!(c.get_selection().unwrap(), r#"a\"b"#);
+        assert_eq!(c.unescape('\\').unwrap(), r#"a"b"#);
+        assert_eq!(c.str().unwrap(), " rest");
+
+        let c = Cursor::from(r#""\\""#).quoted('"', '\\');
+        assert_eq!(c.unescape('\\').unwrap(), "\\");
+        assert_eq!(c.str().unwrap(), "");
+
+        let (c, s) = Cursor::from("'it\\'s'")
+            .quoted('\'', '\\')
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, s), ("", "it\\'s"));
+
+        assert!(Cursor::from(r#""abc"#)
+            .quoted('"', '\\')
+            .validate()
+            .is_err());
+        assert!(Cursor::from(r#""abc\""#)
+            .quoted('"', '\\')
+            .validate()
+            .is_err());
+        assert!(Cursor::from("abc").quoted('"', '\\').validate().is_err());
+    }
+
+    #[test]
+    fn test_alt() {
+        let mut parsers = [
+            |c: Cursor<'static>| c.text("ab").text("X"),
+            |c: Cursor<'static>| c.text("a"),
+            |c: Cursor<'static>| c.text("abc"),
+        ];
+        // the partially matching first alternative does not leak
+        let c = Cursor::from("abc").alt(&mut parsers);
+        assert_eq!(c.str().unwrap(), "bc");
+
+        let e = Cursor::from("xyz")
+            .alt(&mut parsers)
+            .validate()
+            .unwrap_err();
+        assert!(matches!(e, ParsingError::NoMatch { action: "text", .. }));
+
+        let mut parsers = [
+            |c: Cursor<'static>| c.set_error(ParsingError::Fatal(None)),
+            |c: Cursor<'static>| c.text("x"),
+        ];
+        let res = Cursor::from("xyz").alt(&mut parsers).validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(..))));
+
+        let mut none: [fn(Cursor<'static>) -> Cursor<'static>; 0] = [];
+        assert!(Cursor::from("xyz").alt(&mut none).validate().is_err());
+    }
+
+    #[test]
+    fn test_repeat_fold() {
+        let (c, sum) = Cursor::from("1,22,333,x")
+            .repeat_fold(1.., |c| c.digits(1..).text(","), 0, |acc, s| acc + s.len())
+            .validate()
+            .unwrap();
+        assert_eq!(sum, 9);
+        assert_eq!(c, "x");
+
+        // between 2 and 4, exactly
+        let a = |c: Option<&'static str>| c.char('a');
+        assert_eq!(Some("ax").repeat(2..=4, a), None);
+        assert_eq!(Some("aax").repeat(2..=4, a), Some("x"));
+        assert_eq!(Some("aaaaaa").repeat(2..=4, a), Some("aa"));
+    }
+
+    #[test]
+    fn test_repeat_range() {
+        let a = |c: Option<&'static str>| c.char('a');
+        // inputs matching 0, 1, 3 and 6 times
+        let inputs = [Some("x"), Some("ax"), Some("aaax"), Some("aaaaaax")];
+        let results: Vec<_> = inputs.iter().map(|s| s.repeat(0.., a)).collect();
+        assert_eq!(results, [Some("x"), Some("x"), Some("x"), Some("x")]);
+
+        let results: Vec<_> = inputs.iter().map(|s| s.repeat(1.., a)).collect();
+        assert_eq!(results, [None, Some("x"), Some("x"), Some("x")]);
+
+        let results: Vec<_> = inputs.iter().map(|s| s.repeat(2..=4, a)).collect();
+        assert_eq!(results, [None, None, Some("x"), Some("aax")]);
+
+        assert_eq!(Some("").repeat(1.., |c| c.digits(1..)), None);
+        assert!(Cursor::from("")
+            .repeat(1.., |c| c.digits(1..))
+            .validate()
+            .is_err());
+
+        // a lexer that matches without consuming terminates
+        assert_eq!(Some("a").repeat(2.., |c| c.maybe(",")), Some("a"));
+    }
+
+    #[test]
+    fn test_separated_list() {
+        let (c, v) = Cursor::from("1, 2,3,")
+            .separated_list(int, |c| c.text(",").ws())
+            .validate()
+            .unwrap();
+        assert_eq!(v, vec![1, 2, 3]);
+        assert_eq!(c, ",");
+
+        let (c, v) = Cursor::from("7]")
+            .separated_list(int, |c| c.text(","))
+            .validate()
+            .unwrap();
+        assert_eq!((c, v), ("]", vec![7]));
+
+        let (c, v) = Cursor::from("]")
+            .separated_list(int, |c| c.text(","))
+            .validate()
+            .unwrap();
+        assert_eq!((c, v), ("]", vec![]));
+
+        // an item and separator that both match empty must terminate
+        fn maybe_int(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s)
+                .digits(0..)
+                .parse_selection()
+                .validate()
+                .or(Ok((s, 0)))
+        }
+        let (c, v) = Cursor::from("")
+            .separated_list(maybe_int, |c| c.maybe(","))
+            .validate()
+            .unwrap();
+        assert_eq!((c, v), ("", vec![0]));
+
+        // a fatal separator is not mistaken for the end of the list
+        let res = Cursor::from("1;2")
+            .separated_list(int, |c: Cursor<'static>| match c.str() {
+                Ok(s) if s.starts_with(';') => c.set_error(ParsingError::Fatal(None)),
+                _ => c.text(","),
+            })
+            .validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(..))));
+    }
+
+    #[test]
+    fn test_delimited() {
+        let (c, i) = Cursor::from("{42}!")
+            .delimited(|c| c.text("{"), int, |c| c.text("}"))
+            .validate()
+            .unwrap();
+        assert_eq!((c, i), ("!", 42));
+
+        let e = Cursor::from("{42")
+            .delimited(|c| c.text("{"), int, |c| c.text("}"))
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "FailedMatch: (action='delimited' args='close') at byte 3"
+        );
+
+        let e = Cursor::from("42}")
+            .delimited(|c| c.text("{"), int, |c| c.text("}"))
+            .validate()
+            .unwrap_err();
+        assert!(matches!(e, ParsingError::NoMatch { ref args, .. } if args == "open"));
+
+        let fatal = |c: Cursor<'static>| c.set_error(ParsingError::Fatal(None));
+        let res = Cursor::from("{42}")
+            .delimited(fatal, int, |c| c.text("}"))
+            .validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(..))));
+        let res = Cursor::from("{42}")
+            .delimited(|c| c.text("{"), int, fatal)
+            .validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(..))));
+    }
+
+    #[test]
+    fn test_opt() {
+        fn secs(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s)
+                .text(":")
+                .digits(2..=2)
+                .parse_selection()
+                .validate()
+        }
+        fn fatal(_s: &str) -> Result<(&str, i32), ParsingError> {
+            Err(ParsingError::Fatal(None))
+        }
+        let (c, hh, mm, ss) = Cursor::from("12:34:56")
+            .digits(2..=2)
+            .parse_selection::<i32>()
+            .text(":")
+            .digits(2..=2)
+            .parse_selection::<i32>()
+            .opt(secs)
+            .validate()
+            .unwrap();
+        assert_eq!((c, hh, mm, ss), ("", 12, 34, Some(56)));
+
+        let (c, ss) = Cursor::from("X").opt(secs).validate().unwrap();
+        assert_eq!((c, ss), ("X", None));
+
+        let res = Cursor::from("X").opt(fatal).validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(..))));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(Some("dEaDbeEf!").hex_digits(1..), Some("!"));
+        assert_eq!(Some("xyz").hex_digits(1..), None);
+        assert_eq!(Some("").hex_digits(1..), None);
+
+        let (c, i) = Cursor::from("0xFF,")
+            .maybe("0x")
+            .hex_digits(1..)
+            .parse_selection_radix::<u8>(16)
+            .validate()
+            .unwrap();
+        assert_eq!((c, i), (",", 255));
+
+        let (_c, i) = Cursor::from("dEaDbeEf")
+            .hex_digits(1..)
+            .parse_selection_radix::<u32>(16)
+            .validate()
+            .unwrap();
+        assert_eq!(i, 0xdeadbeef);
+
+        let res = Cursor::from("1FF")
+            .hex_digits(1..)
+            .parse_selection_radix::<u8>(16)
+            .validate();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_take_while_until() {
+        assert_eq!(
+            Some("abc123").take_while(|c| c.is_alphabetic()),
+            Some("123")
+        );
+        assert_eq!(Some("123").take_while(|c| c.is_alphabetic()), Some("123"));
+        assert_eq!(Some("key=value").take_until(|c| c == '='), Some("=value"));
+        assert_eq!(Some("key").take_until(|c| c == '='), Some(""));
+        assert_eq!(None.take_until(|c| c == '='), None);
+
+        let (c, key, value) = Cursor::from("key=value;")
+            .take_until(|c| c == '=')
+            .parse_selection_as_str()
+            .text("=")
+            .selection_start()
+            .take_while(|c| c.is_alphanumeric())
+            .selection_end()
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, key, value), (";", "key", "value"));
+    }
+
+    #[test]
+    fn test_end_of_stream() {
+        assert_eq!(Some("").eof(), Some(""));
+        assert_eq!(Some("x").eof(), None);
+        assert_eq!(None.eof(), None);
+
+        // as in the cookbook's TimePeriod, on cursors carrying parsed values
+        let (c, t1, t2) = Cursor::from("13:00-14:30")
+            .chars_any(5..=5)
+            .parse_selection::<String>()
+            .text("-")
+            .chars_any(5..=5)
+            .parse_selection::<String>()
+            .end_of_stream()
+            .eof()
+            .validate()
+            .unwrap();
+        assert_eq!((c, t1.as_str(), t2.as_str()), ("", "13:00", "14:30"));
+
+        let res = Cursor::from("13:00-14:30X")
+            .chars_any(5..=5)
+            .parse_selection::<String>()
+            .text("-")
+            .chars_any(5..=5)
+            .eof()
+            .validate();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_chars_any() {
+        assert_eq!(Some("hello").chars_any(2..=2).str().unwrap(), "llo");
+        assert_eq!(Some("hello").chars_any(0..=3).str().unwrap(), "lo");
+        assert_eq!(Some("hello").chars_any(0..).str().unwrap(), "");
+        assert_eq!(Some("abc").chars_any(2..=5).str().unwrap(), "");
+        assert_eq!(Some("a c").chars_any(3..=3).str().unwrap(), "");
+        assert_eq!(Some("héllo").chars_any(2..=2).str().unwrap(), "llo");
+        assert_eq!(Some("日本語").chars_any(1..=1).str().unwrap(), "本語");
+        assert!(Some("ab").chars_any(3..).str().is_err());
+        assert!(Some("ab").chars_any(..0).str().is_err());
+        assert_eq!(Some("").chars_any(0..=1).str().unwrap(), "");
+
+        let (c, s) = Cursor::from("11:20 PM")
+            .chars_any(5..=5)
+            .parse_selection::<String>();
+        assert_eq!(c.str().unwrap(), " PM");
+        assert_eq!(s.unwrap(), "11:20");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_graphemes() {
+        // "e" + combining acute accent
+        let s = "cafe\u{301} au lait";
+        assert_eq!(Some(s).chars_any(4..=4).str().unwrap(), "\u{301} au lait");
+        assert_eq!(Some(s).graphemes(4..=4).str().unwrap(), " au lait");
+        assert_eq!(Some("ab").graphemes(0..).str().unwrap(), "");
+        assert!(Some("e\u{301}").graphemes(2..).str().is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(Some("hello").bytes(2).str().unwrap(), "llo");
+        assert_eq!(Some("hello").bytes(0).str().unwrap(), "hello");
+        assert_eq!(Some("hello").bytes(5).str().unwrap(), "");
+        assert!(Some("hello").bytes(6).str().is_err());
+        assert_eq!(Some("héllo").bytes(3).str().unwrap(), "llo");
+        // byte 2 is inside 'é'
+        assert!(Some("héllo").bytes(2).str().is_err());
+
+        let (c, s) = Cursor::from("0042ABC").bytes(4).parse_selection::<u32>();
+        assert_eq!(c.str().unwrap(), "ABC");
+        assert_eq!(s.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_named() {
+        let c = Cursor::from("key=value;")
+            .mark("key")
+            .word()
+            .capture_to("key")
+            .char('=')
+            .mark("value")
+            .word()
+            .capture_to("value")
+            .char(';');
+        assert_eq!(c.named("key").unwrap(), "key");
+        assert_eq!(c.named("value").unwrap(), "value");
+        assert!(c.named("other").is_err());
+        assert_eq!(c.str().unwrap(), "");
+
+        // single selection is unaffected
+        let c = Cursor::from("ab cd")
+            .mark("all")
+            .selection_start()
+            .word()
+            .selection_end()
+            .ws()
+            .word()
+            .capture_to("all");
+        assert_eq!(c.get_selection().unwrap(), "ab");
+        assert_eq!(c.named("all").unwrap(), "ab cd");
+
+        // re-marking a name overwrites it
+        let c = Cursor::from("ab cd")
+            .mark("x")
+            .word()
+            .capture_to("x")
+            .ws()
+            .mark("x")
+            .word()
+            .capture_to("x");
+        assert_eq!(c.named("x").unwrap(), "cd");
+
+        // a clone captures independently of the cursor it was cloned from
+        let c = Cursor::from("ab cd").mark("x").word();
+        let d = c.clone().ws().word().capture_to("x");
+        let c = c.capture_to("x");
+        assert_eq!(c.named("x").unwrap(), "ab");
+        assert_eq!(d.named("x").unwrap(), "ab cd");
+
+        // marked but not captured, or never marked
+        let c = Cursor::from("ab").mark("x").word();
+        assert!(c.named("x").is_err());
+        assert!(Cursor::from("ab").capture_to("x").str().is_err());
+
+        let (c, i) = Cursor::from("12 34")
+            .digits(1..)
+            .parse_selection::<i32>()
+            .ws()
+            .mark("n")
+            .digits(1..)
+            .capture_to("n");
+        assert_eq!(i, Some(12));
+        assert_eq!(c.named("n").unwrap(), "34");
+    }
+
+    #[test]
+    fn test_scan_to_any() {
+        let s = "INFO: x WARN: y ERROR: z";
+        assert_eq!(
+            Some(s).scan_to_any(&["ERROR:", "WARN:"]).str().unwrap(),
+            " y ERROR: z"
+        );
+        assert_eq!(
+            Some(s).find_any(&["ERROR:", "WARN:"]).str().unwrap(),
+            "WARN: y ERROR: z"
+        );
+        // tie resolves to the longer needle
+        assert_eq!(Some("abcd").scan_to_any(&["ab", "abc"]).str().unwrap(), "d");
+        assert_eq!(Some("abcd").scan_to_any(&["abc", "ab"]).str().unwrap(), "d");
+        assert!(Some(s).scan_to_any(&["DEBUG"]).str().is_err());
+        assert!(Some(s).find_any(&["DEBUG"]).str().is_err());
+        assert!(Some(s).find_any(&[]).str().is_err());
+    }
+
+    #[test]
+    fn test_map_selection() {
+        let (c, n) = Cursor::from("1,234,567 units")
+            .chars_in(
+                1..,
+                &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ','],
+            )
+            .map_selection(|s| s.replace(',', "").into())
+            .parse_selection::<i32>();
+        assert_eq!(n, Some(1_234_567));
+        assert_eq!(c.str().unwrap(), " units");
+
+        // borrowed mapping
+        let (_c, n) = Cursor::from("+42")
+            .text("+")
+            .digits(1..)
+            .map_selection(|s| s.into())
+            .parse_selection::<i32>();
+        assert_eq!(n, Some(42));
+
+        let (c, n) = Cursor::from("1_0x")
+            .chars_any(4..=4)
+            .map_selection(|s| s.replace('_', "").into())
+            .parse_selection::<i32>();
+        assert!(c.str().is_err());
+        assert_eq!(n, None);
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut nums = vec![];
+        let mut c = Cursor::from("1\nx\n22\ny y\n3\n");
+        while matches!(c.str(), Ok(s) if !s.is_empty()) {
+            c = c.recover(
+                |c| {
+                    let (c, n) = c.digits(1..).parse_selection::<i32>();
+                    nums.extend(n);
+                    c.text("\n")
+                },
+                |c| c.scan_eol(),
+            );
+        }
+        assert_eq!(nums, vec![1, 22, 3]);
+        assert!(c.str().is_ok());
+        assert_eq!(c.errors().len(), 2);
+        assert!(matches!(
+            c.errors()[0],
+            ParsingError::NoMatch { offset: 2, .. }
+        ));
+        assert!(matches!(
+            c.errors()[1],
+            ParsingError::NoMatch { offset: 7, .. }
+        ));
+
+        // fatal errors are not recovered
+        let c = Cursor::from("abc")
+            .recover(|c| c.set_error(ParsingError::Fatal(None)), |c| c.scan_eol());
+        assert!(c.str().is_err());
+        assert!(c.errors().is_empty());
+
+        // recovered errors take part in equality
+        let c = Cursor::from("x");
+        assert_ne!(c.clone(), c.push_error(ParsingError::Fatal(None)));
+    }
+
+    #[test]
+    fn test_bind_failure() {
+        assert!(parse_time_v1("23:X:13.234").is_err());
+        assert!(parse_time_v2("23:59:1x.234").is_err());
+
+        // target left untouched on failure
+        let mut hh = -1_i32;
+        let c = Cursor::from("xx:00")
+            .digits(2..=2)
+            .parse_selection()
+            .bind(&mut hh);
+        assert!(c.validate().is_err());
+        assert_eq!(hh, -1);
+    }
+
+    #[test]
+    fn test_parse_selection_errors() {
+        use crate::error::Recoverable;
+        use std::{marker::PhantomData, str::FromStr};
+
+        // overflow is fatal, and keeps the cause
+        let (c, n) = Cursor::from("999").digits(1..).parse_selection::<u8>();
+        assert_eq!(n, None);
+        let e = c.validate().unwrap_err();
+        assert!(!e.is_recoverable());
+        assert_eq!(
+            e.to_string(),
+            "Fatal:number too large to fit in target type"
+        );
+        assert_eq!(e.clone().to_string(), e.to_string());
+
+        // not a number is recoverable
+        let (c, _n) = Cursor::from("12x").chars_any(3..=3).parse_selection::<u8>();
+        let e = c.validate().unwrap_err();
+        assert!(e.is_recoverable());
+        assert_eq!(
+            e.to_string(),
+            "FailedMatch: (action='parse int error' args='invalid digit') at byte 3"
+        );
+
+        let (c, _n) = Cursor::from("300")
+            .digits(1..)
+            .parse_selection_radix::<u8>(16);
+        assert!(!c.validate().unwrap_err().is_recoverable());
+
+        // any FromStr will do, including one whose error type is not 'static
+        #[derive(Debug)]
+        struct Yes<'x>(PhantomData<&'x ()>);
+        impl<'x> FromStr for Yes<'x> {
+            type Err = &'x str;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                (s == "yes").then_some(Yes(PhantomData)).ok_or("no")
+            }
+        }
+        fn yes<'x>(s: &'x str) -> Result<Yes<'x>, ParsingError> {
+            Cursor::from(s)
+                .word()
+                .parse_selection::<Yes<'x>>()
+                .validate()
+                .map(|(_, y)| y)
+        }
+        assert!(yes("yes").is_ok());
+        assert!(yes("no").unwrap_err().is_recoverable());
+    }
+
+    #[test]
+    fn test_measure() {
+        let (c, n) = Cursor::from("12345x").measure(|c| c.digits(1..));
+        assert_eq!(n, Some(5));
+        assert_eq!(c.str().unwrap(), "x");
+
+        let (_c, n) = Cursor::from("héllo!").measure(|c| c.alphabetics(1..));
+        assert_eq!(n, Some(5));
+
+        let (c, n) = Cursor::from("x").measure(|c| c.digits(1..));
+        assert_eq!(n, None);
+        assert!(c.str().is_err());
+
+        let (c, n) = Cursor::from("1234567890")
+            .measure(|c| c.digits(1..))
+            .validate()
+            .unwrap();
+        assert_eq!((c, n), ("", 10));
+
+        // moving backwards is a failure, not a panic
+        let start = Cursor::from("ab");
+        let cp = start.checkpoint();
+        let (c, n) = start.text("a").measure(|c| c.restore(cp));
+        assert_eq!(n, None);
+        assert!(matches!(c.validate(), Err(ParsingError::NoMatch { .. })));
+    }
+
+    #[test]
+    fn test_selection_range() {
+        let s = "let x = 42;";
+        let c = Cursor::from(s)
+            .text("let")
+            .ws()
+            .selection_start()
+            .word()
+            .selection_end();
+        let r = c.selection_range().unwrap();
+        assert_eq!(r, 4..5);
+        assert_eq!(&s[r], "x");
+
+        let (c, n) = Cursor::from(s)
+            .text("let x = ")
+            .digits(1..)
+            .parse_selection::<i32>();
+        assert_eq!(n, Some(42));
+        assert_eq!(&s[c.selection_range().unwrap()], "42");
+
+        let c = Cursor::from(s).text("var");
+        assert!(c.selection_range().is_err());
+    }
+
+    impl<'a> Parse<'a> for Time {
+        fn parse(c: Cursor<'a>) -> Result<(Cursor<'a>, Self), ParsingError> {
+            let (s, t) = parse_time_v1(c.str()?)?;
+            Ok((c.set_str(s), t))
+        }
+    }
+
+    #[test]
+    fn test_parse_as() {
+        let (c, t) = Cursor::from("at 23:59:13.234 ok")
+            .text("at ")
+            .parse_as::<Time>()
+            .text(" ok")
+            .validate()
+            .unwrap();
+        assert_eq!(c, "");
+        assert_eq!(t, Time(23, 59, 13.234));
+
+        let (c, t) = Cursor::from("at 23:5x").text("at ").parse_as::<Time>();
+        assert!(c.str().is_err());
+        assert_eq!(t, None);
+    }
+
+    #[test]
+    fn test_many() {
+        let (c, v) = Cursor::from("1 2 3 x").many0(int_ws).validate().unwrap();
+        assert_eq!((c, v), ("x", vec![1, 2, 3]));
+
+        let (c, v) = Cursor::from("x").many0(int_ws).validate().unwrap();
+        assert_eq!((c, v), ("x", vec![]));
+
+        let (c, v) = Cursor::from("x").many1(int_ws);
+        assert!(c.str().is_err());
+        assert_eq!(v, None);
+        assert!(Cursor::from("").many1(int_ws).validate().is_err());
+
+        let (c, v) = Cursor::from("1 2 3 4")
+            .many(1..=2, int_ws)
+            .validate()
+            .unwrap();
+        assert_eq!((c, v), ("3 4", vec![1, 2]));
+        assert!(Cursor::from("1 2").many(3.., int_ws).validate().is_err());
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(Some("ab\ncd").rest_of_line().str().unwrap(), "\ncd");
+        assert_eq!(Some("ab\r\ncd").rest_of_line().str().unwrap(), "\r\ncd");
+        assert_eq!(Some("ab").rest_of_line().str().unwrap(), "");
+        assert_eq!(Some("").rest_of_line().str().unwrap(), "");
+
+        assert_eq!(Some("ab\ncd").line().str().unwrap(), "cd");
+        assert_eq!(Some("ab\r\ncd").line().str().unwrap(), "cd");
+        assert_eq!(Some("\n").line().str().unwrap(), "");
+        assert_eq!(Some("cd").line().str().unwrap(), "");
+        assert!(Some("").line().str().is_err());
+
+        let mut lines = vec![];
+        let mut c = Cursor::from("k1=v1\r\nk2=v2\nlast");
+        while c.clone().end_of_stream().str().is_err() {
+            let (cur, s) = c
+                .selection_start()
+                .rest_of_line()
+                .selection_end()
+                .parse_selection::<String>();
+            lines.extend(s);
+            c = cur.maybe("\r").maybe("\n");
+        }
+        assert_eq!(lines, vec!["k1=v1", "k2=v2", "last"]);
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error;
+
+        let (c, _n) = Cursor::from("999").digits(1..).parse_selection::<u8>();
+        let e = c.validate().unwrap_err();
+        assert_eq!(
+            e.source().unwrap().to_string(),
+            "number too large to fit in target type"
+        );
+        assert!(e.clone().source().is_some());
+        assert!(Cursor::from("x")
+            .digits(1..)
+            .validate()
+            .unwrap_err()
+            .source()
+            .is_none());
+    }
+
+    #[test]
+    fn test_int() {
+        let (c, n) = Cursor::from("-42;").int(1..).parse_selection::<i32>();
+        assert_eq!(n, Some(-42));
+        assert_eq!(c.str().unwrap(), ";");
+
+        let (_c, n) = Cursor::from("+7").int(1..).parse_selection::<i32>();
+        assert_eq!(n, Some(7));
+        let (_c, n) = Cursor::from("123").int(..).parse_selection::<i32>();
+        assert_eq!(n, Some(123));
+        let (c, n) = Cursor::from("-12345").int(1..=3).parse_selection::<i32>();
+        assert_eq!(n, Some(-123));
+        assert_eq!(c.str().unwrap(), "45");
+
+        assert!(Some("-").int(1..).str().is_err());
+        assert!(Some("-x").int(1..).str().is_err());
+        assert!(Some("x").int(0..).str().is_err());
+        assert!(Some("-12").int(3..).str().is_err());
+    }
+
+    #[test]
+    fn test_newline() {
+        assert_eq!(Some("\nab").newline().str().unwrap(), "ab");
+        assert_eq!(Some("\r\nab").newline().str().unwrap(), "ab");
+        assert_eq!(Some("\n\nab").newline().str().unwrap(), "\nab");
+        assert!(Some("ab\n").newline().str().is_err());
+        assert!(Some(" \n").newline().str().is_err());
+        assert!(Some("\r").newline().str().is_err());
+        assert!(Some("").newline().str().is_err());
+
+        assert_eq!(Some("\n \t\r\n\n  ab").blank_lines().str().unwrap(), "  ab");
+        assert_eq!(Some("  ab").blank_lines().str().unwrap(), "  ab");
+        assert_eq!(Some("\n  \n  ").blank_lines().str().unwrap(), "");
+    }
+
+    #[test]
+    fn test_verify() {
+        let hour = |s| {
+            Cursor::from(s)
+                .digits(2..=2)
+                .verify_parsed(|hh: &u32| *hh <= 23)
+                .parse_selection::<u32>()
+                .text(":")
+                .validate()
+        };
+        assert_eq!(hour("23:").unwrap(), ("", 23));
+        assert!(hour("24:").is_err());
+        assert!(hour("xx:").is_err());
+
+        let c = Cursor::from("abc1")
+            .alphabetics(1..)
+            .verify(|s| s.len() == 3);
+        assert_eq!(c.str().unwrap(), "1");
+        let c = Cursor::from("abcd1")
+            .alphabetics(1..)
+            .verify(|s| s.len() == 3);
+        assert!(c.str().is_err());
+    }
+
+    #[test]
+    fn test_parse_selection_label() {
+        let (c, _n) = Cursor::from("12:3x")
+            .debug_context("hours")
+            .digits(2..=2)
+            .parse_selection::<u32>()
+            .text(":")
+            .debug_context("minutes")
+            .chars_any(2..=2)
+            .parse_selection::<u32>();
+        let e = c.validate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "FailedMatch: (action='parse int error' args='invalid digit (in minutes)') at byte 5"
+        );
+
+        // the label belongs to the cursor, not to later parses on the same thread
+        let (c, _n) = Cursor::from("x").chars_any(1..=1).parse_selection::<u32>();
+        assert_eq!(
+            c.validate().unwrap_err().to_string(),
+            "FailedMatch: (action='parse int error' args='invalid digit') at byte 1"
+        );
+    }
+
+    #[test]
+    fn test_trim_selection() {
+        let (c, s) = Cursor::from("13   |x")
+            .chars_any(5..=5)
+            .trim_selection()
+            .parse_selection::<u32>();
+        assert_eq!(s, Some(13));
+        assert_eq!(c.str().unwrap(), "|x");
+
+        // subsequent matches select from the cursor, not the trimmed end
+        let (c, s) = c.text("|").chars_any(1..=1).parse_selection::<String>();
+        assert_eq!(s.unwrap(), "x");
+        assert_eq!(c.str().unwrap(), "");
+
+        let c = Cursor::from(" ab cd | ef")
+            .selection_start()
+            .chars_any(7..=7)
+            .selection_end()
+            .trim_selection()
+            .ws();
+        assert_eq!(c.get_selection().unwrap(), "ab cd");
+        assert_eq!(c.str().unwrap(), "| ef");
+
+        let c = Cursor::from("    x").chars_any(3..=3).trim_selection();
+        assert_eq!(c.get_selection().unwrap(), "");
+    }
+
+    #[test]
+    fn test_one_of() {
+        assert_eq!(Some("+1").one_of(&['+', '-']).str().unwrap(), "1");
+        assert_eq!(Some("éa").one_of(&['é']).str().unwrap(), "a");
+        assert!(Some("1").one_of(&['+', '-']).str().is_err());
+        assert!(Some("").one_of(&['+']).str().is_err());
+        assert!(Some("x").one_of(&[]).str().is_err());
+
+        assert_eq!(Some("ab").none_of(&['"']).str().unwrap(), "b");
+        assert_eq!(Some("éa").none_of(&['"']).str().unwrap(), "a");
+        assert!(Some("\"").none_of(&['"']).str().is_err());
+        assert!(Some("").none_of(&['"']).str().is_err());
+    }
+
+    #[test]
+    fn test_expect() {
+        let c = Cursor::from("SELECT x FROM t")
+            .expect("SELECT")
+            .ws()
+            .word()
+            .ws();
+        assert_eq!(c.clone().expect("FROM").str().unwrap(), " t");
+        let e = c.expect("WHERE").validate().unwrap_err();
+        assert!(e.to_string().contains("WHERE"), "{e}");
+        assert_eq!(
+            e.to_string(),
+            "FailedMatch: (action='expect' args='WHERE (found \"FROM t\")') at byte 9"
+        );
+        // as text, input ending partway through the word is Incomplete on a partial cursor
+        let res = Cursor::from("wh").partial().expect("while").validate();
+        assert!(
+            matches!(res, Err(ParsingError::Incomplete { needed: 3 })),
+            "{res:?}"
+        );
+    }
+
+    #[test]
+    fn test_failure_args() {
+        let e = Cursor::from("ab").text("xy").validate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "FailedMatch: (action='text' args='xy (found \"ab\")') at byte 0"
+        );
+        let e = Cursor::from("ab").digits(1..).validate().unwrap_err();
+        assert_eq!(e.clone().to_string(), e.to_string());
+
+        // the found text stops at the end of the line, or of the input
+        let e = Cursor::from("a\nb")
+            .text("a")
+            .text("b")
+            .validate()
+            .unwrap_err();
+        assert!(
+            matches!(e, ParsingError::NoMatch { ref args, .. } if args == "b (found \"\\nb\")")
+        );
+        let e = Cursor::from("a")
+            .text("a")
+            .text("b")
+            .validate()
+            .unwrap_err();
+        assert!(
+            matches!(e, ParsingError::NoMatch { ref args, .. } if args == "b (found end of input)")
+        );
+
+        // args too long to hold inline, or a committed cursor, are formatted at once
+        let word = "abcdefghijklmnopqrstuvwxyz";
+        let e = Cursor::from("ab").text(word).validate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            format!("FailedMatch: (action='text' args='{word} (found \"ab\")') at byte 0")
+        );
+        let e = Cursor::from("ab")
+            .commit()
+            .text("x")
+            .validate()
+            .unwrap_err();
+        assert!(e.to_string().contains("x (found \"ab\")"), "{e}");
+    }
+
+    #[test]
+    fn test_balanced() {
+        let c = Cursor::from("(a(b)c) d").balanced('(', ')');
+        assert_eq!(c.get_selection().unwrap(), "a(b)c");
+        assert_eq!(c.str().unwrap(), " d");
+
+        let c = Cursor::from("{}").balanced('{', '}');
+        assert_eq!(c.get_selection().unwrap(), "");
+        assert_eq!(c.str().unwrap(), "");
+
+        let (_c, s) = Cursor::from("{x: {y: 1}}")
+            .balanced('{', '}')
+            .parse_selection::<String>();
+        assert_eq!(s.unwrap(), "x: {y: 1}");
+
+        assert!(Cursor::from("(a(b)").balanced('(', ')').str().is_err());
+        assert!(Cursor::from(")a(").balanced('(', ')').str().is_err());
+        assert!(Cursor::from("").balanced('(', ')').str().is_err());
+    }
+
+    #[test]
+    fn test_partial() {
+        let e = Cursor::from("12")
+            .partial()
+            .digits(3..=3)
+            .validate()
+            .unwrap_err();
+        assert!(matches!(e, ParsingError::Incomplete { needed: 1 }), "{e}");
+        assert_eq!(e.to_string(), "Incomplete: 1 more needed");
+
+        // unchanged when not partial, or when the match fails before the end
+        let e = Cursor::from("12").digits(3..=3).validate().unwrap_err();
+        assert!(matches!(e, ParsingError::NoMatch { .. }));
+        let e = Cursor::from("12x")
+            .partial()
+            .digits(3..=3)
+            .validate()
+            .unwrap_err();
+        assert!(matches!(e, ParsingError::NoMatch { .. }));
+
+        let (c, n) = Cursor::from("12:3")
+            .partial()
+            .digits(2..=2)
+            .parse_selection::<u32>()
+            .text(":")
+            .chars_any(2..=2);
+        assert_eq!(n, Some(12));
+        assert!(matches!(
+            c.validate(),
+            Err(ParsingError::Incomplete { needed: 1 })
+        ));
+
+        let c = Cursor::from("GE").partial().text("GET");
+        assert!(matches!(
+            c.validate(),
+            Err(ParsingError::Incomplete { needed: 1 })
+        ));
+        let c = Cursor::from("GX").partial().text("GET");
+        assert!(matches!(c.validate(), Err(ParsingError::NoMatch { .. })));
+        let c = Cursor::from("ab").partial().bytes(4);
+        assert!(matches!(
+            c.validate(),
+            Err(ParsingError::Incomplete { needed: 2 })
+        ));
+
+        // alternatives are not tried until more input arrives
+        let mut parsers = [
+            |c: Cursor<'static>| c.text("GET"),
+            |c: Cursor<'static>| c.text("G"),
+        ];
+        let c = Cursor::from("GE").partial().alt(&mut parsers);
+        assert!(matches!(c.validate(), Err(ParsingError::Incomplete { .. })));
+    }
+
+    #[test]
+    fn test_preceded_terminated() {
+        let (c, n) = Cursor::from("$42 off")
+            .preceded(|c| c.text("$"), int)
+            .validate()
+            .unwrap();
+        assert_eq!((c, n), (" off", 42));
+
+        let (c, n) = Cursor::from("15% off")
+            .terminated(int, |c| c.text("%"))
+            .validate()
+            .unwrap();
+        assert_eq!((c, n), (" off", 15));
+
+        assert!(Cursor::from("42")
+            .preceded(|c| c.text("$"), int)
+            .validate()
+            .is_err());
+        let (c, n) = Cursor::from("15 off").terminated(int, |c| c.text("%"));
+        assert!(c.str().is_err());
+        assert_eq!(n, None);
+
+        let (c, _n) = Cursor::from("$x").preceded(
+            |c| c.text("$"),
+            |_c: Cursor<'static>| -> Result<(Cursor<'static>, i32), ParsingError> {
+                Err(ParsingError::Fatal(None))
+            },
+        );
+        assert!(matches!(c.validate(), Err(ParsingError::Fatal(..))));
+    }
+
+    #[test]
+    fn test_push_selection() {
+        let mut out = vec![];
+        let c = Cursor::from("1,2,3")
+            .repeat(1.., |c| c.digits(1..).push_selection(&mut out).maybe(","));
+        assert_eq!(c.str().unwrap(), "");
+        assert_eq!(out, vec!["1", "2", "3"]);
+
+        let mut out = vec![];
+        let c = Cursor::from("x").digits(1..).push_selection(&mut out);
+        assert!(c.str().is_err());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_fold_many() {
+        let sum = |s| {
+            Cursor::from(s)
+                .fold_many(
+                    1..=3,
+                    |c| c.select(|c| c.digits(1..)).maybe(" "),
+                    0,
+                    |acc, s| acc + s.parse::<i32>().unwrap(),
+                )
+                .validate()
+        };
+        assert_eq!(sum("1 20 300").unwrap(), ("", 321));
+        assert_eq!(sum("1 20 300 4").unwrap(), ("4", 321));
+        assert!(sum("x").is_err());
+
+        // folds the selection, not the matched text
+        let (_c, s) = Cursor::from("[a][b][c]")
+            .fold_many(
+                0..,
+                |c| {
+                    c.text("[")
+                        .selection_start()
+                        .word()
+                        .selection_end()
+                        .text("]")
+                },
+                String::new(),
+                |acc, s| acc + s,
+            )
+            .validate()
+            .unwrap();
+        assert_eq!(s, "abc");
+    }
+
+    #[test]
+    fn test_and_then() {
+        let count = |&n: &i32, c: Cursor<'static>| {
+            let (c, v) = c.many(n..=n, int_ws);
+            Ok((c, v.unwrap_or_default()))
+        };
+        let (rest, n, v) = Cursor::from("2 7 8 9")
+            .digits(1..)
+            .parse_selection::<i32>()
+            .ws()
+            .and_then(count)
+            .validate()
+            .unwrap();
+        assert_eq!((rest, n, v), ("9", 2, vec![7, 8]));
+
+        let (c, v) = Cursor::from("3 7 8")
+            .digits(1..)
+            .parse_selection::<i32>()
+            .ws()
+            .and_then(count);
+        assert!(c.0.str().is_err());
+        assert_eq!(v, None);
+
+        let (c, v) = Cursor::from("2 7 8")
+            .digits(1..)
+            .parse_selection::<i32>()
+            .and_then(|_, _| Err::<(_, ()), _>(ParsingError::Fatal(None)));
+        assert!(matches!(c.0.validate(), Err(ParsingError::Fatal(_))));
+        assert_eq!(v, None);
+    }
+
+    #[test]
+    fn test_next_line() {
+        assert_eq!(Cursor::from("ab\ncd").next_line().str().unwrap(), "cd");
+        assert_eq!(Cursor::from("ab\n").next_line().str().unwrap(), "");
+        assert!(Cursor::from("ab").next_line().validate().is_err());
+        assert!(Cursor::from("").next_line().validate().is_err());
+        assert_eq!(Cursor::from("ab").scan_eol().str().unwrap(), "");
+        assert!(matches!(
+            Cursor::from("ab").partial().next_line().validate(),
+            Err(ParsingError::Incomplete { needed: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_iter_struct() {
+        fn int_comma(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s).parse_with(int).maybe(",").validate()
+        }
+        let v: Vec<_> = Cursor::from("1,2,3x")
+            .iter_struct(int_comma)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(v, vec![1, 2, 3]);
+
+        let mut it = Cursor::from("1,2").iter_struct(int_comma);
+        assert_eq!(it.next().unwrap().unwrap(), 1);
+        assert_eq!(it.remaining(), Some("2"));
+        assert_eq!(it.next().unwrap().unwrap(), 2);
+        assert!(it.next().is_none());
+
+        let fatal = |s| match s {
+            "9" => Err(ParsingError::Fatal(None)),
+            s => int_comma(s),
+        };
+        let mut it = Cursor::from("1,9").iter_struct(fatal);
+        assert_eq!(it.next().unwrap().unwrap(), 1);
+        assert!(matches!(it.next(), Some(Err(ParsingError::Fatal(_)))));
+        assert!(it.next().is_none());
+
+        // the error of a failed cursor is yielded, once
+        let mut it = Cursor::from("x").text("y").iter_struct(int_comma);
+        assert!(matches!(it.next(), Some(Err(ParsingError::NoMatch { .. }))));
+        assert!(it.next().is_none());
+
+        // after a NoMatch the parser is not run again
+        let mut calls = 0;
+        let mut it = Cursor::from("1,x").iter_struct(|s| {
+            calls += 1;
+            int_comma(s)
+        });
+        assert_eq!(it.next().unwrap().unwrap(), 1);
+        assert!(it.next().is_none());
+        assert!(it.next().is_none());
+        assert_eq!(it.remaining(), Some("x"));
+        drop(it);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_skip_chars() {
+        assert_eq!(Cursor::from("héllo").skip_chars(2).str().unwrap(), "llo");
+        assert_eq!(Cursor::from("hé").skip_chars(2).str().unwrap(), "");
+        assert_eq!(Cursor::from("hé").skip_chars(0).str().unwrap(), "hé");
+        assert!(Cursor::from("hé").skip_chars(3).validate().is_err());
+
+        let (c, n) = Cursor::from("    x: 1").skip_run(' ');
+        assert_eq!((c.str().unwrap(), n), ("x: 1", Some(4)));
+        let (c, n) = Cursor::from("x").skip_run(' ');
+        assert_eq!((c.str().unwrap(), n), ("x", Some(0)));
+        let (c, n) = Cursor::from("ééa").skip_run('é');
+        assert_eq!((c.str().unwrap(), n), ("a", Some(2)));
+    }
+
+    #[test]
+    fn test_peek_char() {
+        let c = Cursor::from("[1]");
+        assert_eq!(c.peek_char(), Some('['));
+        assert_eq!(c.text("[").peek_char(), Some('1'));
+        assert_eq!(Cursor::from("").peek_char(), None);
+        assert_eq!(Cursor::from("a").text("b").peek_char(), None);
+        let (c, _) = Cursor::from("é1")
+            .chars_any(1..=1)
+            .parse_selection::<char>();
+        assert_eq!(c.peek_char(), Some('1'));
+    }
+
+    #[test]
+    fn test_range_boundaries() {
+        fn rest(s: &'static str, c: Cursor<'static>) -> Option<&'static str> {
+            c.str().map(|r| &s[..s.len() - r.len()]).ok()
+        }
+        for (input, want) in [
+            ("12", None),
+            ("123", Some("123")),
+            ("1234", Some("123")),
+            ("123x", Some("123")),
+        ] {
+            let c = Cursor::from(input).chars_match(3..=3, |c: char| c.is_ascii_digit());
+            assert_eq!(rest(input, c), want, "chars_match(3..=3) on {input}");
+        }
+        for (input, want) in [
+            ("a", None),
+            ("ab", Some("ab")),
+            ("abc", Some("abc")),
+            ("abcd", Some("abc")),
+        ] {
+            let c = Cursor::from(input).between(2, 3, |c| c.is_ascii_hexdigit());
+            assert_eq!(rest(input, c), want, "between(2, 3) on {input}");
+        }
+        for (input, want) in [
+            ("1", None),
+            ("12", Some("12")),
+            ("123", Some("123")),
+            ("1234", Some("1234")),
+            ("12345", Some("1234")),
+            ("12x", Some("12")),
+            ("1x", None),
+        ] {
+            let c = Cursor::from(input).digits(2..=4);
+            assert_eq!(rest(input, c), want, "digits(2..=4) on {input}");
+        }
+        for (input, want) in [
+            ("", Some("")),
+            ("1", Some("1")),
+            ("12", Some("12")),
+            ("123", Some("12")),
+        ] {
+            let c = Cursor::from(input).digits(0..3);
+            assert_eq!(rest(input, c), want, "digits(0..3) on {input}");
+        }
+        assert_eq!(Cursor::from("123").digits(0..).str().unwrap(), "");
+        assert_eq!(Cursor::from("x").digits(0..).str().unwrap(), "x");
+        assert_eq!(Cursor::from("ééé").alphabetics(2..=2).str().unwrap(), "é");
+        assert!(Cursor::from("é1").alphabetics(2..).validate().is_err());
+    }
+
+    #[test]
+    fn test_range_upper_bound() {
+        assert_eq!(Cursor::from("123").digits(2..=2).str().unwrap(), "3");
+        assert_eq!(Cursor::from("12345x").digits(2..=2).str().unwrap(), "345x");
+        assert_eq!(Cursor::from("ffff").hex_digits(..=2).str().unwrap(), "ff");
+        assert_eq!(Cursor::from("abc").alphabetics(1..3).str().unwrap(), "c");
+        assert_eq!(
+            Cursor::from("aaa").chars_in(1..=1, &['a']).str().unwrap(),
+            "aa"
+        );
+        assert_eq!(
+            Cursor::from("abc")
+                .chars_not_in(..=2, &['x'])
+                .str()
+                .unwrap(),
+            "c"
+        );
+        assert_eq!(Cursor::from("-1234").int(..=3).str().unwrap(), "4");
+
+        // fixed width fields run together
+        let (rest, h, m) = Cursor::from("123045")
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .digits(2..=2)
+            .parse_selection::<u8>()
+            .validate()
+            .unwrap();
+        assert_eq!((rest, h, m), ("45", 12, 30));
+    }
+
+    #[test]
+    fn test_scoped() {
+        let label = || crate::log_label();
+        let c = Cursor::from("12:xx").debug_context("timetable");
+        let c = c.scoped("time", |c| {
+            assert_eq!(label(), "time");
+            let (c, _) = c.digits(2..=2).parse_selection::<u8>().text(":");
+            let (c, _) = c.alphabetics(2..=2).parse_selection::<u8>();
+            c
+        });
+        assert_eq!(label(), "timetable");
+        assert!(matches!(
+            c.validate(),
+            Err(ParsingError::NoMatch { ref args, .. }) if args == "invalid digit (in time)"
+        ));
+
+        let c = Cursor::from("12")
+            .debug_context("timetable")
+            .scoped("time", |c| c.digits(2..=2));
+        assert_eq!(c.label(), "timetable");
+    }
+
+    #[test]
+    fn test_or_value() {
+        fn secs(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s)
+                .text(":")
+                .digits(2..=2)
+                .parse_selection()
+                .validate()
+        }
+        let hms = |s| {
+            Cursor::from(s)
+                .digits(2..=2)
+                .parse_selection::<i32>()
+                .text(":")
+                .digits(2..=2)
+                .parse_selection::<i32>()
+                .or_value(secs, 0)
+                .validate()
+        };
+        assert_eq!(hms("12:34:56").unwrap(), ("", 12, 34, 56));
+        assert_eq!(hms("12:34").unwrap(), ("", 12, 34, 0));
+        assert_eq!(hms("12:34:x").unwrap(), (":x", 12, 34, 0));
+
+        let fatal = |_s| Err::<(&str, i32), _>(ParsingError::Fatal(None));
+        let res = Cursor::from("X").or_value(fatal, 0).validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(_))));
+    }
+
+    #[test]
+    fn test_rest() {
+        let (c, subject, body) = Cursor::from("Subject: hi\nline 1\nline 2")
+            .text("Subject: ")
+            .rest_of_line()
+            .parse_selection_as_str()
+            .text("\n")
+            .rest()
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, subject, body), ("", "hi", "line 1\nline 2"));
+
+        let (c, s) = Cursor::from("ab")
+            .text("ab")
+            .rest()
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, s), ("", ""));
+    }
+
+    #[test]
+    fn test_number() {
+        let num = |s| Cursor::from(s).number().validate().unwrap();
+        assert_eq!(num("100"), ("", Number::Int(100)));
+        assert_eq!(num("-12,"), (",", Number::Int(-12)));
+        assert_eq!(num("+7"), ("", Number::Int(7)));
+        assert_eq!(num("1e3"), ("", Number::Float(1000.0)));
+        assert_eq!(num("1.5x"), ("x", Number::Float(1.5)));
+        assert_eq!(num("3."), ("", Number::Float(3.0)));
+        assert_eq!(num("1e"), ("e", Number::Int(1)));
+        assert_eq!(
+            num("99999999999999999999"),
+            ("", Number::Float(99999999999999999999.0))
+        );
+        assert!(Cursor::from("x").number().validate().is_err());
+        assert!(Cursor::from("-").number().validate().is_err());
+        assert_eq!("-4".parse::<Number>().unwrap(), Number::Int(-4));
+        assert!("4x".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn test_left_assoc() {
+        fn op(s: &str) -> Result<(&str, char), ParsingError> {
+            Cursor::from(s)
+                .ws()
+                .chars_in(1..=1, &['+', '-'])
+                .parse_selection()
+                .validate()
+        }
+        let eval = |a, op, b| if op == '+' { a + b } else { a - b };
+        let calc = |s| Cursor::from(s).left_assoc(ws_int, op, eval).validate();
+        assert_eq!(calc("1-2-3").unwrap(), ("", -4));
+        assert_eq!(calc("10 + 2 - 3;").unwrap(), (";", 9));
+        assert_eq!(calc("7").unwrap(), ("", 7));
+        assert_eq!(calc("7 - x").unwrap(), (" - x", 7));
+        assert!(calc("x").is_err());
+    }
+
+    #[test]
+    fn test_parse_selection_hex_bytes() {
+        let hex = |s| {
+            Cursor::from(s)
+                .hex_digits(1..)
+                .parse_selection_hex_bytes()
+                .validate()
+        };
+        assert_eq!(
+            hex("deadBEEF;").unwrap(),
+            (";", vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(hex("00").unwrap(), ("", vec![0]));
+        assert!(matches!(
+            hex("abc"),
+            Err(ParsingError::NoMatch { ref args, .. }) if args == "odd length"
+        ));
+        let res = Cursor::from("+1")
+            .chars_any(2..=2)
+            .parse_selection_hex_bytes()
+            .validate();
+        assert!(matches!(
+            res,
+            Err(ParsingError::NoMatch { ref args, .. }) if args == "invalid hex"
+        ));
+    }
+
+    #[test]
+    fn test_expect_eof() {
+        assert_eq!(
+            Cursor::from("12").digits(1..).expect_eof().str().unwrap(),
+            ""
+        );
+        let res = Cursor::from("12 garbage")
+            .digits(1..)
+            .expect_eof()
+            .validate();
+        assert!(
+            matches!(res, Err(ParsingError::NoMatch { action: "expect_eof", ref args, offset: 2 }) if args == "unconsumed | garbage|"),
+            "{res:?}"
+        );
+    }
+
+    #[test]
+    fn test_recognize() {
+        let (c, s) = Cursor::from("-1.5e3 m")
+            .recognize(|c| c.float())
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, s), (" m", "-1.5e3"));
+
+        let (c, s) = Cursor::from("key = value;")
+            .recognize(|c| c.word().ws().text("=").ws().word())
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, s), (";", "key = value"));
+
+        assert!(Cursor::from("key value")
+            .recognize(|c| c.word().text("="))
+            .parse_selection_as_str()
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_append_last_error() {
+        let mut v: Vec<i32> = vec![];
+        let mut c = Cursor::from("1,2,X,4");
+        for _ in 0..4 {
+            c = c.chars_not_in(1.., &[',']).append_last(&mut v).maybe(",");
+        }
+        assert_eq!(v, vec![1, 2]);
+        let res = c.validate();
+        assert!(
+            matches!(res, Err(ParsingError::NoMatch { action: "append_last", ref args, offset: 5 }) if args == "X"),
+            "{res:?}"
+        );
+    }
+
+    #[test]
+    fn test_skip_bom() {
+        let c = Cursor::from("\u{FEFF}London,1");
+        assert!(c.clone().text("London").validate().is_err());
+        assert_eq!(c.skip_bom().text("London").str().unwrap(), ",1");
+        assert_eq!(Cursor::from("London").skip_bom().str().unwrap(), "London");
+        assert_eq!(Cursor::from("").skip_bom().str().unwrap(), "");
+    }
+
+    #[test]
+    fn test_indent() {
+        let (c, col) = Cursor::from("a:\n  b: 1\n\tc").line().indent();
+        assert_eq!(col, Some(3));
+        let c = c.at_column(3).word().text(":").line();
+        assert!(c.clone().at_column(2).validate().is_err());
+        let (c, col) = c.indent();
+        assert_eq!((c.str().unwrap(), col), ("c", Some(2)));
+        assert_eq!(Cursor::from("x").indent().1, Some(1));
+        assert!(Cursor::from("x").at_column(1).validate().is_ok());
+
+        // column agrees with line_col, including after multi-byte chars
+        let c = Cursor::from("ab\né x").text("ab\né ");
+        assert_eq!(c.column(), c.line_col().1);
+        assert_eq!(c.column(), 3);
+        // a sub-parser on a &str counts from where it began
+        let (_c, col) = Cursor::from("ab  x").text("ab").parse_with(|s| {
+            let (c, col) = Cursor::from(s).indent();
+            c.validate().map(|c| (c, col.unwrap_or_default()))
+        });
+        assert_eq!(col, Some(3));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex() {
+        let email = regex::Regex::new(r"[\w.]+@[\w.]+").unwrap();
+        let (c, s) = Cursor::from("ann@example.com, bob")
+            .regex(&email)
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, s), (", bob", "ann@example.com"));
+        // anchored at the cursor, not found later in the input
+        assert!(Cursor::from("to: ann@example.com")
+            .regex(&email)
+            .validate()
+            .is_err());
+
+        // a match at the end of a long non-matching tail doesn't count
+        let time = regex::Regex::new(r"\d+:\d+").unwrap();
+        let input = format!("x{}12:34", "y".repeat(100_000));
+        assert!(Cursor::from(input.as_str())
+            .regex(&time)
+            .validate()
+            .is_err());
+        let e = Cursor::from("x").regex(&time).validate().unwrap_err();
+        assert!(e.to_string().contains(r"args='\d+:\d+ (found"), "{e}");
+
+        // the options of a RegexBuilder are kept
+        let abc = regex::RegexBuilder::new("abc")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        assert_eq!(Cursor::from("ABC!").regex(&abc).validate().unwrap(), "!");
+    }
+
+    #[test]
+    fn test_take_until_parser() {
+        let timestamp = |c: Cursor<'static>| c.text("\n").digits(2..=2).text(":").digits(2..=2);
+        let log = "09:00 start\nline a\nline b\n09:05 next";
+        let (c, body) = Cursor::from(log)
+            .digits(2..=2)
+            .text(":")
+            .digits(2..=2)
+            .take_until_parser(timestamp)
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!(body, " start\nline a\nline b");
+        assert_eq!(c, "\n09:05 next");
+
+        let (c, body) = Cursor::from("no timestamp")
+            .take_until_parser(timestamp)
+            .parse_selection_as_str()
+            .validate()
+            .unwrap();
+        assert_eq!((c, body), ("", "no timestamp"));
+
+        let fatal = |c: Cursor<'static>| c.set_error(ParsingError::Fatal(None));
+        let res = Cursor::from("abc").take_until_parser(fatal).validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(_))));
+    }
+
+    #[test]
+    fn test_parse_struct_vec_to_min() {
+        fn stmt(s: &str) -> Result<(&str, String), ParsingError> {
+            Cursor::from(s)
+                .word()
+                .parse_selection()
+                .text(";")
+                .validate()
+        }
+        let mut v = vec![];
+        let c = Cursor::from("a;b;}")
+            .parse_struct_vec_to_min(stmt, &mut v, 1)
+            .unwrap();
+        assert_eq!(
+            (c.str().unwrap(), v),
+            ("}", vec!["a".to_string(), "b".to_string()])
+        );
+
+        let mut v = vec![];
+        let res = Cursor::from("}").parse_struct_vec_to_min(stmt, &mut v, 1);
+        assert!(matches!(res, Err(ParsingError::NoMatch { .. })));
+
+        // the error names this method, and is located where the list ended
+        let mut v = vec![];
+        let res = Cursor::from("a;b;}").parse_struct_vec_to_min(stmt, &mut v, 3);
+        assert!(
+            matches!(
+                res,
+                Err(ParsingError::NoMatch {
+                    action: "parse_struct_vec_to_min",
+                    offset: 4,
+                    ..
+                })
+            ),
+            "{res:?}"
+        );
+
+        // a partial cursor is Incomplete only if the structs ran out with the input
+        let mut v = vec![];
+        let res = Cursor::from("a;?")
+            .partial()
+            .parse_struct_vec_to_min(stmt, &mut v, 3);
+        assert!(
+            matches!(res, Err(ParsingError::NoMatch { ref args, .. }) if args == "expected at least 3, found 1"),
+            "{res:?}"
+        );
+        let res = Cursor::from("a;")
+            .partial()
+            .parse_struct_vec_to_min(stmt, &mut v, 3);
+        assert!(
+            matches!(res, Err(ParsingError::Incomplete { .. })),
+            "{res:?}"
+        );
+        assert!(Cursor::from("}").parse_struct_vec_to(stmt, &mut v).is_ok());
+    }
+
+    #[test]
+    fn test_text_alt_error() {
+        let res = Cursor::from("x").text_alt(&["{", "[", "\""]).validate();
+        assert!(
+            matches!(res, Err(ParsingError::NoMatch { action: "text_alt", ref args, .. }) if args == r#"one of ["{", "[", "\""]"#),
+            "{res:?}"
+        );
+        assert_eq!(
+            Cursor::from("[1]").text_alt(&["{", "["]).str().unwrap(),
+            "1]"
+        );
+    }
+
+    #[test]
+    fn test_ws1() {
+        let arrive = |s| {
+            Cursor::from(s)
+                .text("Arrive")
+                .ws1()
+                .digits(2..=2)
+                .validate()
+        };
+        assert_eq!(arrive("Arrive 11").unwrap(), "");
+        assert_eq!(arrive("Arrive\n\t11").unwrap(), "");
+        assert!(arrive("Arrive11").is_err());
+        assert_eq!(Cursor::from(" \tx").hws1().str().unwrap(), "x");
+        assert!(Cursor::from("\nx").hws1().validate().is_err());
+        assert!(Cursor::from("").ws1().validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_selection_or() {
+        let ms = |s| {
+            Cursor::from(s)
+                .chars_not_in(0.., &['s'])
+                .parse_selection_or(|| 0.0)
+                .text("s")
+                .validate()
+        };
+        assert_eq!(ms("0.25s").unwrap(), ("", 0.25));
+        assert_eq!(ms("0.2x5s").unwrap(), ("", 0.0));
+        assert_eq!(ms("s").unwrap(), ("", 0.0));
+        assert!(ms("0.25").is_err());
+
+        // overflow is fatal, not a malformed field
+        let res = Cursor::from("999")
+            .digits(1..)
+            .parse_selection_or(|| 0u8)
+            .validate();
+        assert!(matches!(res, Err(ParsingError::Fatal(_))), "{res:?}");
+    }
+
+    #[test]
+    fn test_commit() {
+        fn if_stmt(c: Cursor<'static>) -> Cursor<'static> {
+            c.text("if").commit().ws1().text("(").word().text(")")
+        }
+        fn call(c: Cursor<'static>) -> Cursor<'static> {
+            c.word().text("()")
+        }
+        let stmt = |s| {
+            let mut branches: [fn(Cursor<'static>) -> Cursor<'static>; 2] = [if_stmt, call];
+            Cursor::from(s).alt(&mut branches).validate()
+        };
+        assert_eq!(stmt("if (x)").unwrap(), "");
+        assert_eq!(stmt("foo()").unwrap(), "");
+        // the missing "(" is reported, rather than backtracking to try a call
+        let res = stmt("if x");
+        assert!(matches!(res, Err(ParsingError::Fatal(Some(_)))), "{res:?}");
+        assert!(res.unwrap_err().to_string().contains("'text'"));
+
+        // the flag is cleared by the error it promotes
+        let c = Cursor::from("ab").commit().text("x");
+        assert!(matches!(c.validate(), Err(ParsingError::Fatal(_))));
+        let c = Cursor::from("ab").commit().text("a").text("b");
+        assert_eq!(c.str().unwrap(), "");
+
+        // the commit ends with its branch, so the next statement can still backtrack
+        let mut branches: [fn(Cursor<'static>) -> Cursor<'static>; 2] = [if_stmt, call];
+        let c = Cursor::from("if (x);foo()")
+            .alt(&mut branches)
+            .text(";")
+            .alt(&mut branches);
+        assert_eq!(c.validate().unwrap(), "");
+
+        // within a committed branch, alternatives and optional parts still backtrack..
+        fn paren(c: Cursor<'static>) -> Cursor<'static> {
+            c.text("(")
+        }
+        fn x(c: Cursor<'static>) -> Cursor<'static> {
+            c.text("x")
+        }
+        let args = |s| {
+            let mut args: [fn(Cursor<'static>) -> Cursor<'static>; 2] = [paren, x];
+            Cursor::from(s).text("if").commit().ws().alt(&mut args)
+        };
+        let c = args("if x").not(|c| c.text("y"));
+        assert!(c.is_committed());
+        assert_eq!(c.validate().unwrap(), "");
+        let one = |c: Cursor<'static>| {
+            let c = c.text("1");
+            c.clone().validate().map(|_| (c, 1))
+        };
+        let (c, v) = Cursor::from("x").commit().opt(one);
+        assert_eq!((c.is_committed(), v), (true, Some(None)));
+        // ..but their failure as a whole is fatal
+        assert!(matches!(
+            args("if z").validate(),
+            Err(ParsingError::Fatal(_))
+        ));
+    }
+
+    #[test]
+    fn test_escaped() {
+        let string = |s| {
+            Cursor::from(s)
+                .text("\"")
+                .escaped(
+                    |c| c != '"',
+                    '\\',
+                    |c| match c {
+                        'n' => Some('\n'),
+                        't' => Some('\t'),
+                        '"' | '\\' => Some(c),
+                        _ => None,
+                    },
+                )
+                .text("\"")
+                .validate()
+        };
+        assert_eq!(
+            string(r#""a\tb\n" x"#).unwrap(),
+            (" x", "a\tb\n".to_string())
+        );
+        assert_eq!(
+            string(r#""say \"hi\"""#).unwrap(),
+            ("", r#"say "hi""#.to_string())
+        );
+        assert_eq!(string(r#""""#).unwrap(), ("", String::new()));
+        let res = string(r#""a\qb""#);
+        assert!(
+            matches!(res, Err(ParsingError::NoMatch { action: "escaped", ref args, offset: 2 }) if args == r"unknown escape \q"),
+            "{res:?}"
+        );
+        assert!(string(r#""a\"#).is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let field = |s| {
+            Cursor::from(s)
+                .token(&[',', ';'])
+                .parse_selection::<String>()
+                .validate()
+        };
+        assert_eq!(field("ab c,d").unwrap(), (",d", "ab c".to_string()));
+        assert_eq!(field("héllo").unwrap(), ("", "héllo".to_string()));
+        assert!(field(";x").is_err());
+        assert!(field("").is_err());
+    }
+
+    #[test]
+    fn test_boolean() {
+        let flag = |s| Cursor::from(s).boolean_default().validate();
+        assert_eq!(flag("Yes").unwrap(), ("", true));
+        assert_eq!(flag("OFF;").unwrap(), (";", false));
+        assert_eq!(flag("0").unwrap(), ("", false));
+        assert!(flag("maybe").is_err());
+        // only whole words match
+        assert!(flag("only").is_err());
+        assert!(flag("nothing").is_err());
+        assert!(flag("10").is_err());
+        // no false words, so "no" is not a boolean
+        let res = Cursor::from("no").boolean(&["yes"], &[]).validate();
+        assert!(res.is_err());
+        let (c, b) = Cursor::from("enabled")
+            .boolean(&["en", "enabled"], &["disabled"])
+            .validate()
+            .unwrap();
+        assert_eq!((c, b), ("", true));
+    }
+
+    #[test]
+    fn test_list() {
+        fn comma(c: Cursor) -> Cursor {
+            c.ws().text(",")
+        }
+        let list = |s, t| Cursor::from(s).list(ws_int, comma, t).validate();
+        assert_eq!(list("1, 2]", Trailing::Forbid).unwrap(), ("]", vec![1, 2]));
+        // the item expected after the separator is reported
+        let res = list("1, 2,]", Trailing::Forbid);
+        assert!(
+            matches!(
+                res,
+                Err(ParsingError::NoMatch {
+                    action: "digits",
+                    offset: 5,
+                    ..
+                })
+            ),
+            "{res:?}"
+        );
+        assert_eq!(list("]", Trailing::Forbid).unwrap(), ("]", vec![]));
+        assert_eq!(list("1, 2]", Trailing::Allow).unwrap(), ("]", vec![1, 2]));
+        assert_eq!(list("1, 2,]", Trailing::Allow).unwrap(), ("]", vec![1, 2]));
+        assert_eq!(
+            list("1, 2,]", Trailing::Require).unwrap(),
+            ("]", vec![1, 2])
+        );
+        assert!(list("1, 2]", Trailing::Require).is_err());
+
+        // an item and separator that both match empty must terminate
+        fn maybe_int(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s)
+                .digits(0..)
+                .parse_selection()
+                .validate()
+                .or(Ok((s, 0)))
+        }
+        let res = Cursor::from("")
+            .list(maybe_int, |c| c.maybe(","), Trailing::Forbid)
+            .validate();
+        assert_eq!(res.unwrap(), ("", vec![0]));
+    }
+
+    #[test]
+    fn test_attempt() {
+        let (c, res) = Cursor::from("12:30").attempt(int);
+        assert_eq!(res.unwrap(), 12);
+        assert_eq!(c.str().unwrap(), ":30");
+
+        let (c, res) = Cursor::from("x1").attempt(int);
+        assert!(matches!(res, Err(ParsingError::NoMatch { .. })));
+        assert_eq!(c.text("x").attempt(int).1.unwrap(), 1);
+
+        let fatal =
+            |_s: &str| -> Result<(&str, i32), ParsingError> { Err(ParsingError::Fatal(None)) };
+        let (c, res) = Cursor::from("1").attempt(fatal);
+        assert!(res.is_err());
+        assert!(c.validate().is_err());
+    }
+
+    #[test]
+    fn test_repeat_until() {
+        fn stmt(s: &str) -> Result<(&str, String), ParsingError> {
+            Cursor::from(s)
+                .alphabetics(1..)
+                .parse_selection()
+                .text(";")
+                .validate()
+        }
+        let block = |s| {
+            Cursor::from(s)
+                .repeat_until(stmt, |c| c.text("end"))
+                .validate()
+        };
+        let (c, v) = block("a;b;end.").unwrap();
+        assert_eq!((c, v), (".", vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(block("end").unwrap(), ("", vec![]));
+        assert!(block("a;b").is_err());
+        assert!(block("a;1;end").is_err());
+    }
+
+    #[test]
+    fn test_signed() {
+        let int = |s| {
+            Cursor::from(s)
+                .signed(|c| c.digits(1..))
+                .parse_selection::<i32>()
+                .validate()
+        };
+        assert_eq!(int("-12 x").unwrap(), (" x", -12));
+        assert_eq!(int("+7").unwrap(), ("", 7));
+        assert_eq!(int("42").unwrap(), ("", 42));
+        assert!(int("-x").is_err());
+        assert!(int("--1").is_err());
+        let (_c, f) = Cursor::from("-1.5e2")
+            .signed(|c| c.float())
+            .parse_selection::<f64>()
+            .validate()
+            .unwrap();
+        assert_eq!(f, -150.0);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let time = |c: Cursor<'static>| c.digits(2..=2).text(":").digits(2..=2);
+        let e = Cursor::from("at 12-30")
+            .annotate("event", |c| c.text("at ").annotate("time", time))
+            .validate()
+            .unwrap_err();
+        let ParsingError::NoMatch {
+            action,
+            args,
+            offset,
+        } = e
+        else {
+            panic!("{e:?}")
+        };
+        assert_eq!(action, "text");
+        assert_eq!(
+            args,
+            "while parsing <event>: while parsing <time>: : (found \"-30\")"
+        );
+        assert_eq!(offset, 5);
+        assert!(Cursor::from("12:30")
+            .annotate("time", time)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_map_value() {
+        let (c, h, m) = Cursor::from("09:30")
+            .digits(2..=2)
+            .parse_selection::<i32>()
+            .map_value(|h| h as u8)
+            .text(":")
+            .digits(2..=2)
+            .parse_selection::<i32>()
+            .map_value(|m| m * 60)
+            .validate()
+            .unwrap();
+        assert_eq!((c, h, m), ("", 9_u8, 1800));
+        let (c, v) = Cursor::from("x")
+            .digits(1..)
+            .parse_selection::<i32>()
+            .map_value(|i| i + 1);
+        assert!(c.str().is_err() && v.is_none());
+    }
+
+    #[test]
+    fn test_skip_comments() {
+        let skip = |s| {
+            Cursor::from(s)
+                .skip_comments("#", Some(("/*", "*/")))
+                .validate()
+        };
+        assert_eq!(skip(" # one\n  /* two\n */ x # y").unwrap(), "x # y");
+        assert_eq!(skip("/**/\n#").unwrap(), "");
+        assert_eq!(skip("/* a /* b */ c */").unwrap(), "c */");
+        let e = skip("  /* open");
+        assert!(matches!(e, Err(ParsingError::Fatal(_))), "{e:?}");
+
+        let c = Cursor::from("/* a /* b */ c */ x").skip_nested_comments("", Some(("/*", "*/")));
+        assert_eq!(c.str().unwrap(), "x");
+        let c = Cursor::from("/* a /* b */ x").skip_nested_comments("//", Some(("/*", "*/")));
+        assert!(c.validate().is_err());
+        let c = Cursor::from("// a\n// b\nx").skip_comments("//", None);
+        assert_eq!(c.str().unwrap(), "x");
+    }
+
+    #[test]
+    fn test_parse_selection_with() {
+        // minutes since midnight from "hh:mm"
+        fn minutes(s: &str) -> Result<u32, ParsingError> {
+            let (h, m) = s.split_once(':').ok_or_else(|| ParsingError::default())?;
+            Ok(h.parse::<u32>()? * 60 + m.parse::<u32>()?)
+        }
+        let at = |s| {
+            Cursor::from(s)
+                .chars_not_in(1.., &[' '])
+                .parse_selection_with(minutes)
+                .validate()
+        };
+        assert_eq!(at("01:30 pm").unwrap(), (" pm", 90));
+        assert!(at("0130 pm").is_err());
+        assert!(at("01:x").is_err());
+    }
+
+    #[test]
+    fn test_text_alt_longest() {
+        let ops = ["<", "<=", "<<", "="];
+        let op = |s| Cursor::from(s).text_alt_longest(&ops).validate();
+        assert_eq!(op("<= 3").unwrap(), " 3");
+        assert_eq!(op("<<1").unwrap(), "1");
+        assert_eq!(op("< 3").unwrap(), " 3");
+        assert!(op("> 3").is_err());
+        assert_eq!(Cursor::from("<= 3").text_alt(&ops).str().unwrap(), "= 3");
+    }
+
+    #[test]
+    fn test_fail_succeed() {
+        let hour = |s, h24: bool| {
+            let c = Cursor::from(s)
+                .digits(2..=2)
+                .verify_parsed(|h: &u32| *h <= 23);
+            let c = if h24 {
+                c.succeed()
+            } else {
+                c.fail("12 hour clock")
+            };
+            c.validate()
+        };
+        assert_eq!(hour("13:00", true).unwrap(), ":00");
+        let e = hour("13:00", false).unwrap_err();
+        assert!(
+            matches!(
+                e,
+                ParsingError::NoMatch {
+                    action: "12 hour clock",
+                    offset: 2,
+                    ..
+                }
+            ),
+            "{e:?}"
+        );
+        assert_eq!(Cursor::from("ab").succeed().str().unwrap(), "ab");
+    }
+
+    #[test]
+    fn test_count_mismatch() {
+        let args = |c: Cursor| match c.validate() {
+            Err(ParsingError::NoMatch { action, args, .. }) => format!("{action}: {args}"),
+            r => panic!("{r:?}"),
+        };
+        assert_eq!(
+            args(Cursor::from("1:").digits(2..=2)),
+            "digits: expected 2, found 1"
+        );
+        assert_eq!(
+            args(Cursor::from("1").digits(2..=2)),
+            "digits: expected 2, found 1"
+        );
+        assert_eq!(
+            args(Cursor::from("ab1").alphabetics(3..)),
+            "alphabetics: expected at least 3, found 2"
+        );
+        assert_eq!(
+            args(Cursor::from("x").digits(1..=4)),
+            "digits: expected 1 to 4, found 0"
+        );
+        assert_eq!(
+            args(Cursor::from("ab").chars_any(3..)),
+            "chars_any: expected at least 3, found 2"
+        );
+    }
+
+    #[test]
+    fn test_date_time_iso() {
+        let date = |s| {
+            Cursor::from(s)
+                .date_iso()
+                .parse_selection_as_str()
+                .validate()
+        };
+        assert_eq!(date("2023-12-31T").unwrap(), ("T", "2023-12-31"));
+        assert_eq!(date("2024-02-29").unwrap(), ("", "2024-02-29"));
+        assert!(date("2023-02-29").is_err());
+        assert!(date("2023-13-01").is_err());
+        assert!(date("2023-04-31").is_err());
+        assert!(date("2023-1-01").is_err());
+
+        let time = |s| {
+            Cursor::from(s)
+                .time_iso()
+                .parse_selection_as_str()
+                .validate()
+        };
+        assert_eq!(time("23:59:59 x").unwrap(), (" x", "23:59:59"));
+        assert_eq!(time("12:30:00.125Z").unwrap(), ("", "12:30:00.125Z"));
+        assert_eq!(time("12:30:00-05:30").unwrap(), ("", "12:30:00-05:30"));
+        assert!(time("24:00:00").is_err());
+        assert!(time("12:30").is_err());
+        assert!(time("12:30:00.").is_err());
+        assert!(time("12:30:00+25:00").is_err());
+
+        // into a tuple, using the selection
+        let (_c, ymd) = Cursor::from("2023-06-15")
+            .date_iso()
+            .parse_selection_with(|s| {
+                let f = |r: std::ops::Range<usize>| s[r].parse::<u32>();
+                Ok((f(0..4)?, f(5..7)?, f(8..10)?))
+            })
+            .validate()
+            .unwrap();
+        assert_eq!(ymd, (2023, 6, 15));
+    }
 }
 
 //     assert_eq!(
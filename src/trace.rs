@@ -0,0 +1,200 @@
+//! Records each matcher call (feature "debug-trace") so that a parse can be
+//! replayed as an indented tree with [`Cursor::trace_report`](crate::prelude::Cursor::trace_report).
+//! Calls are nested under the call that made them, and grouped under the most
+//! recent `debug_context` label. Recording stops after a fixed number of calls
+//! until the report is taken. The trace is held in a thread local, so without the
+//! `std` feature nothing is recorded and the report is empty.
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::fmt::{Debug, Write};
+
+use crate::util;
+
+// a forgotten trace stops growing after this many calls
+const MAX_NODES: usize = 100_000;
+
+struct Node {
+    group: bool,
+    depth: usize,
+    call: String,
+    input: Option<String>,
+    outcome: String,
+}
+
+#[derive(Default)]
+struct Trace {
+    // calls awaiting their success/failure: name and index of their node, innermost last
+    open: Vec<(String, Option<usize>)>,
+    nodes: Vec<Node>,
+    // whether calls are indented under a debug_context group
+    grouped: bool,
+    dropped: usize,
+}
+
+impl Trace {
+    fn push(&mut self, node: Node) -> Option<usize> {
+        if self.nodes.len() >= MAX_NODES {
+            self.dropped += 1;
+            return None;
+        }
+        self.nodes.push(node);
+        Some(self.nodes.len() - 1)
+    }
+
+    fn depth(&self) -> usize {
+        self.open.len() + usize::from(self.grouped)
+    }
+}
+
+#[cfg(feature = "std")]
+thread_local!(static TRACE: RefCell<Trace> = RefCell::new(Trace::default()));
+
+#[cfg(feature = "std")]
+fn with_trace<R: Default>(f: impl FnOnce(&mut Trace) -> R) -> R {
+    TRACE.with(|t| f(&mut t.borrow_mut()))
+}
+
+#[cfg(not(feature = "std"))]
+fn with_trace<R: Default>(_f: impl FnOnce(&mut Trace) -> R) -> R {
+    R::default()
+}
+
+fn call_name(msg: &str) -> &str {
+    msg.trim_start_matches("----> ")
+}
+
+/// opens a call, under which the calls it makes are nested. A cursor already in
+/// error (input None) does nothing, so no call is opened
+pub(crate) fn enter<A: Debug>(msg: &str, args: &A, input: Option<&str>) {
+    let Some(input) = input else {
+        return;
+    };
+    with_trace(|t| {
+        let name = call_name(msg);
+        let node = Node {
+            group: false,
+            depth: t.depth(),
+            call: format!("{name}({args:?})"),
+            input: Some(util::formatter_str(input).trim_end().to_string()),
+            outcome: String::new(),
+        };
+        let i = t.push(node);
+        t.open.push((name.to_string(), i));
+    });
+}
+
+/// closes the innermost open call of that name (and any calls left open within it),
+/// or records a call that was never opened
+pub(crate) fn exit<A: Debug>(msg: &str, args: &A, outcome: String) {
+    with_trace(|t| {
+        let name = call_name(msg);
+        match t.open.iter().rposition(|(n, _)| n == name) {
+            Some(pos) => {
+                let i = t.open[pos].1;
+                t.open.truncate(pos);
+                if let Some(n) = i.map(|i| &mut t.nodes[i]) {
+                    n.call = format!("{name}({args:?})");
+                    n.outcome = outcome;
+                }
+            }
+            None => {
+                let node = Node {
+                    group: false,
+                    depth: t.depth(),
+                    call: format!("{name}({args:?})"),
+                    input: None,
+                    outcome,
+                };
+                t.push(node);
+            }
+        }
+    });
+}
+
+pub(crate) fn group(name: &'static str) {
+    with_trace(|t| {
+        let node = Node {
+            group: true,
+            depth: t.open.len(),
+            call: name.to_string(),
+            input: None,
+            outcome: String::new(),
+        };
+        t.push(node);
+        t.grouped = true;
+    });
+}
+
+/// renders (and clears) the calls recorded on this thread
+pub(crate) fn report() -> String {
+    let trace = with_trace(core::mem::take);
+    let mut s = String::new();
+    for n in trace.nodes {
+        let indent = "  ".repeat(n.depth);
+        if n.group {
+            let _ = writeln!(s, "{indent}[{}]", n.call);
+            continue;
+        }
+        let _ = match n.input {
+            Some(inp) => writeln!(s, "{indent}{} {inp} -> {}", n.call, n.outcome),
+            None => writeln!(s, "{indent}{} -> {}", n.call, n.outcome),
+        };
+    }
+    if trace.dropped > 0 {
+        let _ = writeln!(s, "... {} more calls not recorded", trace.dropped);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use test_log::test;
+
+    #[test]
+    fn test_trace_report() {
+        let _ = Cursor::trace_report();
+        let _ = Cursor::from("12:30")
+            .debug_context("time")
+            .digits(2..=2)
+            .text(":")
+            .text("x");
+        let report = Cursor::trace_report();
+        let lines: Vec<_> = report.lines().skip_while(|l| *l != "[time]").collect();
+        assert_eq!(lines[0], "[time]", "{report}");
+        assert_eq!(lines[1], "  digits(2..=2) |12:30| -> |:30|");
+        assert_eq!(lines[2], "  text(\":\") |:30| -> |30|");
+        assert!(
+            lines[3].starts_with("  text(\"x\") |30| -> NoMatch"),
+            "{report}"
+        );
+        assert!(Cursor::trace_report().is_empty());
+    }
+
+    #[test]
+    fn test_trace_nested() {
+        let _ = Cursor::trace_report();
+        fn int(s: &str) -> Result<(&str, i32), ParsingError> {
+            Cursor::from(s).digits(1..).parse_selection().validate()
+        }
+        let _ = Cursor::from("(1)x")
+            .delimited(|c| c.text("("), int, |c| c.text(")"))
+            .text("y");
+        let report = Cursor::trace_report();
+        let lines: Vec<_> = report.lines().collect();
+        let pos = |l: &str| lines.iter().position(|x| *x == l).unwrap();
+        let delimited = pos("delimited(\"\") |(1)x| -> |x|");
+        assert!(pos("  text(\"(\") |(1)x| -> |1)x|") > delimited, "{report}");
+        assert!(pos("  parse_selection(\"i32\") |)x| -> 1") > delimited);
+        assert!(pos("  text(\")\") |)x| -> |x|") > delimited);
+        assert!(lines
+            .last()
+            .unwrap()
+            .starts_with("text(\"y\") |x| -> NoMatch"));
+    }
+}
@@ -1,3 +1,8 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
 // use crate::prelude::Matchable;
 
 // pub fn formatter<'a, C: Matchable<'a>>(c: &C) -> String {
@@ -6,9 +11,16 @@
 // }
 
 pub fn formatter_str(c: &str) -> String {
-    let s = c[..c.len().min(33)].escape_default().to_string();
+    // truncate on a char boundary
+    let c = c.char_indices().nth(33).map_or(c, |(i, _)| &c[..i]);
+    let s = c.escape_default().to_string();
     let s = s.replace("\\\"", "\"");
     let s = s.replace("\\\'", "\'");
     let s = &s[..s.len().min(33)];
     format!("{:<35}", "|".to_string() + s + "|")
 }
+
+/// describes leftover input in an error, eg "unconsumed |trailing text|"
+pub(crate) fn unconsumed(s: &str) -> String {
+    format!("unconsumed {}", formatter_str(s).trim_end())
+}